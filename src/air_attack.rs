@@ -0,0 +1,345 @@
+// the player's attacks in the air: a slash that keeps the jump's momentum, a plunge
+// straight down and a kunai throw. each has its own hit window from
+// assets/data/air_attacks.ron. the ground attack is still in creature.rs. kunais belong
+// to the thrower's faction and fly through creatures they can't hurt.
+
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use heron::SensorShape;
+use serde::Deserialize;
+
+use crate::controller::{Controller, MoveState};
+use crate::creature::{DamageEvent, PLAYER_ATTACK_KEY};
+use crate::dialogue::DialogueState;
+use crate::faction::FactionRules;
+use crate::hurt::AttackerKind;
+use crate::shop::ShopState;
+use crate::{Allegiance, AnimState, AppState, AttackPoints, Creature, Layer, Player, Projectile};
+
+const AIR_ATTACKS_SOURCE: &str = include_str!("../assets/data/air_attacks.ron");
+
+const KEY_THROW: KeyCode = KeyCode::F;
+const KEY_DOWN: KeyCode = KeyCode::S;
+
+const KUNAI_TEXTURE: &str = "ninja/png/Kunai.png";
+// the texture is 32x160 and points up
+const KUNAI_SCALE: f32 = 0.2;
+const KUNAI_HALF_SIZE: (f32, f32) = (3.0, 15.0);
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AirAttackKind {
+    Slash,
+    Plunge,
+    Throw,
+}
+
+impl AirAttackKind {
+    pub fn anim_state(self) -> AnimState {
+        match self {
+            AirAttackKind::Slash | AirAttackKind::Plunge => AnimState::JumpAttack,
+            AirAttackKind::Throw => AnimState::JumpThrow,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct HitboxDesc {
+    offset: (f32, f32),
+    half_size: (f32, f32),
+}
+
+#[derive(Deserialize, Debug)]
+struct KunaiDesc {
+    speed: f32,
+    // seconds before a kunai that hit nothing disappears
+    lifetime: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct AirAttackDesc {
+    // none: until the player is out of the air
+    duration: Option<f32>,
+    hit_start: f32,
+    // none: until the attack ends
+    hit_end: Option<f32>,
+    #[serde(default)]
+    hitbox: Option<HitboxDesc>,
+    damage: f32,
+    // downwards speed the attack holds the player at
+    #[serde(default)]
+    fall_speed: Option<f32>,
+    // thrown once, at hit_start
+    #[serde(default)]
+    kunai: Option<KunaiDesc>,
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Debug)]
+struct AirAttacks(HashMap<AirAttackKind, AirAttackDesc>);
+
+impl AirAttacks {
+    fn load() -> Self {
+        AirAttacks(ron::de::from_str(AIR_ATTACKS_SOURCE).expect("assets/data/air_attacks.ron is malformed"))
+    }
+}
+
+struct KunaiMaterial(Handle<ColorMaterial>);
+
+////////////////////
+// Resources end //
+////////////////////
+
+//////////////////////
+// Components start
+//////////////////////
+
+// the air attack under way, if any
+#[derive(Default, Debug)]
+pub struct AirAttack {
+    kind: Option<AirAttackKind>,
+    // seconds since the key press
+    elapsed: f32,
+    // each creature is hit once per attack
+    hit: Vec<Entity>,
+    thrown: bool,
+}
+
+impl AirAttack {
+    pub fn kind(&self) -> Option<AirAttackKind> {
+        self.kind
+    }
+
+    fn start(&mut self, kind: AirAttackKind) {
+        *self = AirAttack {
+            kind: Some(kind),
+            ..Default::default()
+        };
+    }
+
+    fn end(&mut self) {
+        if self.kind.is_some() {
+            *self = AirAttack::default();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Kunai {
+    damage: f32,
+    lifetime: Timer,
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct AirAttackPlugin;
+
+impl Plugin for AirAttackPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(AirAttacks::load())
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_kunai_material.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(air_attack.system().label("air_attack").after("controller"))
+                    .with_system(kunai_handler.system()),
+            );
+    }
+}
+
+fn init_kunai_material(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let texture = asset_server.load(KUNAI_TEXTURE);
+    commands.insert_resource(KunaiMaterial(materials.add(texture.into())));
+}
+
+// starts, runs and ends the player's air attacks. runs after the controller, so a plunge
+// can take over the velocity it set.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn air_attack(
+    mut commands: Commands,
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    attacks: Res<AirAttacks>,
+    kunai_material: Res<KunaiMaterial>,
+    dialogue: Res<DialogueState>,
+    shop: Res<ShopState>,
+    mut players: Query<
+        (
+            &Controller,
+            &mut AirAttack,
+            &mut Velocity,
+            &Transform,
+            &TextureAtlasSprite,
+            &AttackPoints,
+            &Allegiance,
+        ),
+        With<Player>,
+    >,
+    creatures: Query<(Entity, &Transform), (With<Creature>, Without<Player>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let blocked = dialogue.is_open() || shop.is_open();
+
+    for (controller, mut attack, mut velocity, transform, sprite, attack_points, allegiance) in players.iter_mut() {
+        // landing or grabbing a wall ends any air attack
+        if controller.state != MoveState::Air {
+            attack.end();
+            continue;
+        }
+        if attack.kind.is_none() && !blocked {
+            if keys.just_pressed(KEY_THROW) {
+                attack.start(AirAttackKind::Throw);
+            } else if keys.just_pressed(PLAYER_ATTACK_KEY) {
+                attack.start(if keys.pressed(KEY_DOWN) {
+                    AirAttackKind::Plunge
+                } else {
+                    AirAttackKind::Slash
+                });
+            }
+        }
+        let desc = match attack.kind.and_then(|kind| attacks.0.get(&kind)) {
+            Some(desc) => desc,
+            None => continue,
+        };
+
+        attack.elapsed += time.delta_seconds();
+        if matches!(desc.duration, Some(duration) if attack.elapsed > duration) {
+            attack.end();
+            continue;
+        }
+        if let Some(fall_speed) = desc.fall_speed {
+            let linear = Vec3::new(0.0, -fall_speed, 0.0);
+            if velocity.linear != linear {
+                velocity.linear = linear;
+            }
+        }
+        let active = attack.elapsed >= desc.hit_start && !matches!(desc.hit_end, Some(end) if attack.elapsed > end);
+        if !active {
+            continue;
+        }
+
+        // the ninja sprite faces right unless flipped
+        let facing = if sprite.flip_x { -1.0 } else { 1.0 };
+        let position = transform.translation.truncate();
+        let damage = attack_points.0 * desc.damage;
+
+        if let Some(hitbox) = &desc.hitbox {
+            // creatures with their center inside the box
+            let center = position + Vec2::new(hitbox.offset.0 * facing, hitbox.offset.1);
+            for (entity, creature) in creatures.iter() {
+                let offset = creature.translation.truncate() - center;
+                if offset.x.abs() <= hitbox.half_size.0
+                    && offset.y.abs() <= hitbox.half_size.1
+                    && !attack.hit.contains(&entity)
+                {
+                    attack.hit.push(entity);
+                    damage_events.send(DamageEvent {
+                        target: entity,
+                        amount: damage,
+                        attacker: AttackerKind::Player,
+                        allegiance: *allegiance,
+                        source: Some(position),
+                    });
+                }
+            }
+        }
+
+        if let Some(kunai) = &desc.kunai {
+            if !attack.thrown {
+                attack.thrown = true;
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        material: kunai_material.0.clone(),
+                        transform: Transform {
+                            translation: transform.translation,
+                            rotation: Quat::from_rotation_z(-FRAC_PI_2 * facing),
+                            scale: Vec3::new(KUNAI_SCALE, KUNAI_SCALE, 1.0),
+                        },
+                        ..Default::default()
+                    })
+                    .insert(Projectile)
+                    .insert(*allegiance)
+                    .insert(Kunai {
+                        damage,
+                        lifetime: Timer::from_seconds(kunai.lifetime, false),
+                    })
+                    .insert(RigidBody::Dynamic)
+                    .insert(CollisionShape::Cuboid {
+                        half_extends: Vec3::new(KUNAI_HALF_SIZE.0, KUNAI_HALF_SIZE.1, 1.0),
+                        border_radius: None,
+                    })
+                    .insert(SensorShape)
+                    .insert(Layer::Projectiles.collision_layers())
+                    .insert(Velocity::from_linear(Vec3::new(facing * kunai.speed, 0.0, 0.0)));
+            }
+        }
+    }
+}
+
+// kunais hurt the first creature they can hit and break on the level's solids and platforms
+fn kunai_handler(
+    mut commands: Commands,
+    time: Res<Time>,
+    rules: Res<FactionRules>,
+    mut events: EventReader<CollisionEvent>,
+    mut kunais: Query<(Entity, &mut Kunai, &Allegiance, &Transform)>,
+    creatures: Query<&Allegiance, With<Creature>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let mut spent = Vec::new();
+    for event in events.iter() {
+        if let CollisionEvent::Started(d1, d2) = event {
+            for (data, other) in [(d1, d2), (d2, d1)].iter() {
+                let entity = data.rigid_body_entity();
+                if spent.contains(&entity) {
+                    continue;
+                }
+                let (kunai, allegiance, transform) = match kunais.get_mut(entity) {
+                    Ok((_, kunai, allegiance, transform)) => (kunai, *allegiance, transform),
+                    Err(_) => continue,
+                };
+                match Layer::of(other) {
+                    Some(Layer::Enemies) | Some(Layer::Allies) => {
+                        let target = other.rigid_body_entity();
+                        if matches!(creatures.get(target), Ok(other_allegiance) if !rules.can_hurt(allegiance, *other_allegiance))
+                        {
+                            continue;
+                        }
+                        damage_events.send(DamageEvent {
+                            target,
+                            amount: kunai.damage,
+                            attacker: AttackerKind::Kunai,
+                            allegiance,
+                            source: Some(transform.translation.truncate()),
+                        });
+                    }
+                    Some(Layer::World) | Some(Layer::Platforms) => {}
+                    _ => continue,
+                }
+                spent.push(entity);
+            }
+        }
+    }
+
+    for (entity, mut kunai, _, _) in kunais.iter_mut() {
+        if kunai.lifetime.tick(time.delta()).finished() && !spent.contains(&entity) {
+            spent.push(entity);
+        }
+    }
+    for entity in spent {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,210 @@
+// sprite animation from data. clips (assets/data/animations.ron) list their frames, how
+// long each is shown and whether they loop, play once or go back and forth. any entity
+// with a texture atlas sprite and an Animator plays them, switching clips is up to the
+// entity's own systems (player_animation in lib.rs, creature.rs).
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{AppState, AtlasHandles};
+
+const ANIMATIONS_SOURCE: &str = include_str!("../assets/data/animations.ron");
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlayMode {
+    Loop,
+    // stays on the last frame
+    Once,
+    PingPong,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum ClipSource {
+    // every image in the folder
+    Folder(String),
+    // prefix000.png, prefix001.png, ...
+    Numbered(String, usize),
+}
+
+impl ClipSource {
+    pub fn load(&self, asset_server: &AssetServer) -> Vec<HandleUntyped> {
+        match self {
+            ClipSource::Folder(path) => asset_server
+                .load_folder(path.as_str())
+                .unwrap_or_else(|_| panic!("animation folder {} is missing", path)),
+            ClipSource::Numbered(prefix, count) => (0..*count)
+                .map(|frame| asset_server.load_untyped(format!("{}{:03}.png", prefix, frame).as_str()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ClipDesc {
+    #[serde(default)]
+    pub source: Option<ClipSource>,
+    // empty: every frame of the atlas
+    #[serde(default)]
+    frames: Vec<u32>,
+    frame_time: f32,
+    // per frame, overrides frame_time
+    #[serde(default)]
+    durations: Vec<f32>,
+    mode: PlayMode,
+}
+
+impl ClipDesc {
+    fn len(&self, atlas: Option<&TextureAtlas>) -> usize {
+        if self.frames.is_empty() {
+            atlas.map(|atlas| atlas.textures.len()).unwrap_or(0)
+        } else {
+            self.frames.len()
+        }
+    }
+
+    fn index(&self, frame: usize) -> u32 {
+        self.frames.get(frame).copied().unwrap_or(frame as u32)
+    }
+
+    fn duration(&self, frame: usize) -> f32 {
+        self.durations.get(frame).copied().unwrap_or(self.frame_time)
+    }
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Debug)]
+pub struct AnimationClips(pub HashMap<String, ClipDesc>);
+
+impl AnimationClips {
+    fn load() -> Self {
+        AnimationClips(ron::de::from_str(ANIMATIONS_SOURCE).expect("assets/data/animations.ron is malformed"))
+    }
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+//////////////////////
+// Components start
+//////////////////////
+
+// plays a clip on the entity's texture atlas sprite
+#[derive(Debug)]
+pub struct Animator {
+    clip: String,
+    // position in the clip's frames
+    frame: usize,
+    // seconds the frame has been shown
+    elapsed: f32,
+    // ping-pong clips on their way back
+    reverse: bool,
+    // the clip hasn't been shown yet
+    fresh: bool,
+}
+
+impl Animator {
+    pub fn new(clip: &str) -> Self {
+        Animator {
+            clip: clip.to_string(),
+            frame: 0,
+            elapsed: 0.0,
+            reverse: false,
+            fresh: true,
+        }
+    }
+
+    // starts another clip from its first frame. the clip that is playing keeps playing.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            *self = Animator::new(clip);
+        }
+    }
+
+    // starts a clip from its first frame, also when it is playing already
+    pub fn restart(&mut self, clip: &str) {
+        *self = Animator::new(clip);
+    }
+
+    fn advance(&mut self, mode: PlayMode, len: usize) {
+        let last = len - 1;
+        match mode {
+            PlayMode::Loop => self.frame = (self.frame + 1) % len,
+            PlayMode::Once => self.frame = (self.frame + 1).min(last),
+            PlayMode::PingPong => {
+                if self.frame == 0 {
+                    self.reverse = false;
+                } else if self.frame >= last {
+                    self.reverse = true;
+                }
+                self.frame = if self.reverse { self.frame - 1 } else { self.frame + 1 };
+            }
+        }
+    }
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(AnimationClips::load())
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(animate.system().label("animate").after("player_animation")),
+            );
+    }
+}
+
+// clips with a source bring their own atlas (built in lib.rs), it is swapped in when they start
+fn animate(
+    time: Res<Time>,
+    clips: Res<AnimationClips>,
+    atlas_handles: Res<AtlasHandles>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut query: Query<(&mut Animator, &mut TextureAtlasSprite, &mut Handle<TextureAtlas>)>,
+) {
+    for (mut animator, mut sprite, mut atlas) in query.iter_mut() {
+        let clip = match clips.0.get(&animator.clip) {
+            Some(clip) => clip,
+            None => continue,
+        };
+        if animator.fresh {
+            animator.fresh = false;
+            if let Some(handle) = atlas_handles.clips.get(&animator.clip) {
+                if *atlas != *handle {
+                    *atlas = handle.clone();
+                }
+            }
+        } else {
+            animator.elapsed += time.delta_seconds();
+        }
+
+        let len = clip.len(texture_atlases.get(&*atlas));
+        if len == 0 {
+            continue;
+        }
+        // a long frame can swallow several short ones
+        while len > 1 && animator.elapsed >= clip.duration(animator.frame) {
+            let duration = clip.duration(animator.frame);
+            if duration <= 0.0 || (clip.mode == PlayMode::Once && animator.frame == len - 1) {
+                break;
+            }
+            animator.elapsed -= duration;
+            animator.advance(clip.mode, len);
+        }
+
+        let index = clip.index(animator.frame.min(len - 1));
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use wasm_lib as lib;
 
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
     lib::run();
 }
 
+// on wasm the page starts the game through the javascript api (web.rs), once it has
+// registered its callbacks
+#[cfg(target_arch = "wasm32")]
+fn main() {}
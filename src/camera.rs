@@ -0,0 +1,443 @@
+// the follow camera. the player moves freely inside a deadzone around the camera's focus,
+// the camera eases towards the focus plus a look-ahead in the running direction and stays
+// inside the level bounds (assets/data/level.ron). the tuning is in assets/data/camera.ron.
+// no physics body, the camera's transform is set directly.
+//
+// on top of following: trauma based shake, smooth zoom and scripted pans, all started
+// with events. the player getting hurt and creatures going down shake the camera, level
+// triggers start the pans listed in camera.ron. after a pan the camera follows again.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::render::camera::{Camera, CameraProjection, OrthographicProjection};
+use heron::prelude::*;
+use serde::Deserialize;
+
+use crate::creature::CreatureDefeatedEvent;
+use crate::hurt::HurtEvent;
+use crate::level::{self, LevelData};
+use crate::trigger::TriggerEvent;
+use crate::{AppState, Player, StaticEntities, StaticEntityId, WIN_HEIGHT, WIN_WIDTH};
+
+const CAMERA_SOURCE: &str = include_str!("../assets/data/camera.ron");
+
+// zoom this close to its goal is snapped to it
+const ZOOM_EPSILON: f32 = 0.001;
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    // slow start and end
+    InOut,
+    // fast start, slow end
+    Out,
+}
+
+impl Easing {
+    // t runs from 0 to 1
+    fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::InOut => t * t * (3.0 - 2.0 * t),
+            Easing::Out => 1.0 - (1.0 - t).powi(3),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ShakeDesc {
+    max_offset: f32,
+    max_angle: f32,
+    frequency: f32,
+    decay: f32,
+    hurt: f32,
+    defeat: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct PanDesc {
+    target: (f32, f32),
+    #[serde(default)]
+    zoom: Option<f32>,
+    duration: f32,
+    hold: f32,
+    easing: Easing,
+    // only the first time the trigger fires
+    #[serde(default)]
+    once: bool,
+}
+
+// shakes the camera, trauma adds up to at most 1
+#[derive(Debug, Copy, Clone)]
+pub struct CameraShakeEvent {
+    pub trauma: f32,
+}
+
+// eases the camera to a projection scale, below 1 is closer
+#[derive(Debug, Copy, Clone)]
+pub struct CameraZoomEvent {
+    pub zoom: f32,
+}
+
+// moves the camera to a point of interest and back to the player
+#[derive(Debug, Copy, Clone)]
+pub struct CameraPanEvent {
+    // world position
+    pub target: Vec2,
+    // zoom while there, the zoom from before comes back afterwards
+    pub zoom: Option<f32>,
+    // seconds on the way there
+    pub duration: f32,
+    // seconds spent there
+    pub hold: f32,
+    pub easing: Easing,
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Deserialize, Debug)]
+struct CameraTuning {
+    deadzone: (f32, f32),
+    damping: f32,
+    look_ahead: f32,
+    look_ahead_damping: f32,
+    look_ahead_min_speed: f32,
+    vertical_offset: f32,
+    zoom_damping: f32,
+    shake: ShakeDesc,
+    #[serde(default)]
+    pans: HashMap<String, PanDesc>,
+}
+
+impl CameraTuning {
+    fn load() -> Self {
+        ron::de::from_str(CAMERA_SOURCE).expect("assets/data/camera.ron is malformed")
+    }
+}
+
+// world rectangle the camera's view stays in, none for no limits
+#[derive(Default, Debug)]
+struct CameraBounds(Option<(Vec2, Vec2)>);
+
+#[derive(Debug)]
+struct Pan {
+    from: Vec2,
+    event: CameraPanEvent,
+    elapsed: f32,
+    // zoom once the pan is over
+    return_zoom: f32,
+}
+
+#[derive(Debug)]
+struct CameraEffects {
+    trauma: f32,
+    // the projection scale the camera eases to
+    zoom: f32,
+    pan: Option<Pan>,
+    // seconds, drives the shake
+    clock: f32,
+}
+
+impl Default for CameraEffects {
+    fn default() -> Self {
+        CameraEffects {
+            trauma: 0.0,
+            zoom: 1.0,
+            pan: None,
+            clock: 0.0,
+        }
+    }
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+//////////////////////
+// Components start
+//////////////////////
+
+// the camera follows the player while this is on it
+#[derive(Default, Debug)]
+pub struct CameraFollow {
+    // point the deadzone is centered on
+    focus: Vec2,
+    // current sideways look-ahead, eased towards the running direction
+    look_ahead: f32,
+    // where the camera is, before shaking
+    center: Vec2,
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(CameraTuning::load())
+            .init_resource::<CameraBounds>()
+            .init_resource::<CameraEffects>()
+            .add_event::<CameraShakeEvent>()
+            .add_event::<CameraZoomEvent>()
+            .add_event::<CameraPanEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_bounds.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Ready)
+                    .with_system(center_camera.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(camera_cues.system().label("camera_cues"))
+                    .with_system(camera_follow.system().label("camera_follow").after("controller"))
+                    .with_system(camera_effects.system().after("camera_follow").after("camera_cues")),
+            );
+    }
+}
+
+fn init_bounds(level: Res<LevelData>, mut bounds: ResMut<CameraBounds>) {
+    bounds.0 = level
+        .bounds
+        .as_ref()
+        .map(|desc| (level::to_world(desc.min, 0.0).truncate(), level::to_world(desc.max, 0.0).truncate()));
+}
+
+// half the size of the logical view (display.rs) in world units. with bars the camera shows
+// more, but the bars hide it.
+fn view_half_size(projection: &OrthographicProjection) -> Vec2 {
+    Vec2::new(WIN_WIDTH, WIN_HEIGHT) * projection.scale / 2.0
+}
+
+// keeps the view inside the bounds, a view larger than the bounds is centered on them
+fn clamp_to_bounds(center: Vec2, half_size: Vec2, bounds: &CameraBounds) -> Vec2 {
+    let (min, max) = match bounds.0 {
+        Some(bounds) => bounds,
+        None => return center,
+    };
+    let clamp = |value: f32, half: f32, min: f32, max: f32| {
+        if max - min <= 2.0 * half {
+            (min + max) / 2.0
+        } else {
+            value.max(min + half).min(max - half)
+        }
+    };
+    Vec2::new(clamp(center.x, half_size.x, min.x, max.x), clamp(center.y, half_size.y, min.y, max.y))
+}
+
+// the player is spawned during setup, the camera starts out on top of them.
+fn center_camera(
+    tuning: Res<CameraTuning>,
+    bounds: Res<CameraBounds>,
+    players: Query<&Transform, With<Player>>,
+    mut cameras: Query<(&mut Transform, &mut CameraFollow, &OrthographicProjection), Without<Player>>,
+) {
+    if let Some(player) = players.iter().next() {
+        let focus = player.translation.truncate() + Vec2::new(0.0, tuning.vertical_offset);
+        for (mut transform, mut follow, projection) in cameras.iter_mut() {
+            let center = clamp_to_bounds(focus, view_half_size(projection), &bounds);
+            *follow = CameraFollow {
+                focus,
+                look_ahead: 0.0,
+                center,
+            };
+            transform.translation = center.extend(transform.translation.z);
+        }
+    }
+}
+
+// turns what happens in the game into camera effects
+#[allow(clippy::too_many_arguments)]
+fn camera_cues(
+    tuning: Res<CameraTuning>,
+    static_entities: Res<StaticEntities>,
+    mut hurt_events: EventReader<HurtEvent>,
+    mut defeated_events: EventReader<CreatureDefeatedEvent>,
+    mut trigger_events: EventReader<TriggerEvent>,
+    mut shake_events: EventWriter<CameraShakeEvent>,
+    mut pan_events: EventWriter<CameraPanEvent>,
+    mut played: Local<Vec<String>>,
+) {
+    let player = static_entities.handles.get(&StaticEntityId::Player).copied();
+    for event in hurt_events.iter() {
+        if Some(event.target) == player {
+            shake_events.send(CameraShakeEvent {
+                trauma: tuning.shake.hurt,
+            });
+        }
+    }
+    for _ in defeated_events.iter() {
+        shake_events.send(CameraShakeEvent {
+            trauma: tuning.shake.defeat,
+        });
+    }
+    for event in trigger_events.iter() {
+        let desc = match tuning.pans.get(&event.id) {
+            Some(desc) => desc,
+            None => continue,
+        };
+        if desc.once {
+            if played.contains(&event.id) {
+                continue;
+            }
+            played.push(event.id.clone());
+        }
+        pan_events.send(CameraPanEvent {
+            target: level::to_world(desc.target, 0.0).truncate(),
+            zoom: desc.zoom,
+            duration: desc.duration,
+            hold: desc.hold,
+            easing: desc.easing,
+        });
+    }
+}
+
+// moves the camera's center after the player, unless a pan is under way
+#[allow(clippy::type_complexity)]
+fn camera_follow(
+    time: Res<Time>,
+    tuning: Res<CameraTuning>,
+    bounds: Res<CameraBounds>,
+    effects: Res<CameraEffects>,
+    players: Query<(&Transform, &Velocity), With<Player>>,
+    mut cameras: Query<(&mut CameraFollow, &OrthographicProjection)>,
+) {
+    let (player, velocity) = match players.iter().next() {
+        Some(player) => player,
+        None => return,
+    };
+    let delta = time.delta_seconds();
+    let target = player.translation.truncate() + Vec2::new(0.0, tuning.vertical_offset);
+
+    for (mut follow, projection) in cameras.iter_mut() {
+        // drag the focus along once the player leaves the deadzone
+        let offset = target - follow.focus;
+        let deadzone = Vec2::new(tuning.deadzone.0, tuning.deadzone.1);
+        let outside = offset.abs() - deadzone;
+        if outside.x > 0.0 {
+            follow.focus.x += outside.x * offset.x.signum();
+        }
+        if outside.y > 0.0 {
+            follow.focus.y += outside.y * offset.y.signum();
+        }
+
+        // look ahead where the player runs, keep looking that way while they stand
+        let look_ahead = if velocity.linear.x.abs() >= tuning.look_ahead_min_speed {
+            tuning.look_ahead * velocity.linear.x.signum()
+        } else {
+            follow.look_ahead
+        };
+        follow.look_ahead += (look_ahead - follow.look_ahead) * ease(tuning.look_ahead_damping, delta);
+
+        if effects.pan.is_some() {
+            continue;
+        }
+        let goal = clamp_to_bounds(
+            follow.focus + Vec2::new(follow.look_ahead, 0.0),
+            view_half_size(projection),
+            &bounds,
+        );
+        let center = follow.center;
+        follow.center += (goal - center) * ease(tuning.damping, delta);
+    }
+}
+
+// runs pans, zoom and shake, then places the camera
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn camera_effects(
+    time: Res<Time>,
+    tuning: Res<CameraTuning>,
+    bounds: Res<CameraBounds>,
+    mut effects: ResMut<CameraEffects>,
+    mut shake_events: EventReader<CameraShakeEvent>,
+    mut zoom_events: EventReader<CameraZoomEvent>,
+    mut pan_events: EventReader<CameraPanEvent>,
+    mut cameras: Query<(&mut Transform, &mut CameraFollow, &mut OrthographicProjection, &mut Camera)>,
+) {
+    let delta = time.delta_seconds();
+    for event in shake_events.iter() {
+        effects.trauma = (effects.trauma + event.trauma).min(1.0);
+    }
+    for event in zoom_events.iter() {
+        match effects.pan.as_mut() {
+            Some(pan) => pan.return_zoom = event.zoom,
+            None => effects.zoom = event.zoom,
+        }
+    }
+    effects.trauma = (effects.trauma - tuning.shake.decay * delta).max(0.0);
+    effects.clock += delta;
+
+    for (mut transform, mut follow, mut projection, mut camera) in cameras.iter_mut() {
+        for event in pan_events.iter() {
+            // a pan cut short by another one still returns to the zoom from before both
+            let return_zoom = match effects.pan.take() {
+                Some(pan) => pan.return_zoom,
+                None => effects.zoom,
+            };
+            if let Some(zoom) = event.zoom {
+                effects.zoom = zoom;
+            }
+            effects.pan = Some(Pan {
+                from: follow.center,
+                event: *event,
+                elapsed: 0.0,
+                return_zoom,
+            });
+        }
+
+        let mut finished = None;
+        if let Some(pan) = effects.pan.as_mut() {
+            pan.elapsed += delta;
+            let t = if pan.event.duration > 0.0 {
+                pan.elapsed / pan.event.duration
+            } else {
+                1.0
+            };
+            let target = clamp_to_bounds(pan.event.target, view_half_size(&projection), &bounds);
+            follow.center = pan.from.lerp(target, pan.event.easing.apply(t));
+            if pan.elapsed >= pan.event.duration + pan.event.hold {
+                finished = Some(pan.return_zoom);
+            }
+        }
+        // camera_follow takes over from here
+        if let Some(zoom) = finished {
+            effects.pan = None;
+            effects.zoom = zoom;
+        }
+
+        if (projection.scale - effects.zoom).abs() > ZOOM_EPSILON {
+            projection.scale += (effects.zoom - projection.scale) * ease(tuning.zoom_damping, delta);
+            if (projection.scale - effects.zoom).abs() <= ZOOM_EPSILON {
+                projection.scale = effects.zoom;
+            }
+            // bevy only rebuilds the matrix when the window changes
+            camera.projection_matrix = projection.get_projection_matrix();
+        }
+
+        let shake = effects.trauma * effects.trauma;
+        let phase = effects.clock * tuning.shake.frequency;
+        // two sines at unrelated speeds per axis, so the shake doesn't look like a loop
+        let wobble = |speed: f32, offset: f32| ((phase * speed + offset).sin() + (phase * speed * 2.3).sin()) / 2.0;
+        let offset = Vec2::new(wobble(1.0, 0.0), wobble(1.3, 1.9)) * tuning.shake.max_offset * shake;
+        let rotation = Quat::from_rotation_z(tuning.shake.max_angle * shake * (phase * 0.9 + 2.6).sin());
+        let translation = (follow.center + offset).extend(transform.translation.z);
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+// share of the way to the goal covered this frame, the same whatever the frame rate
+fn ease(rate: f32, delta: f32) -> f32 {
+    1.0 - (-rate * delta).exp()
+}
@@ -0,0 +1,224 @@
+// treasure chests: opened with the interact key when the player is close enough,
+// drop items rolled from a weighted loot table (assets/data/loot.ron).
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::inventory::{spawn_pickup, ItemKind};
+use crate::level::{self, LevelData};
+use crate::dialogue::DialogueState;
+use crate::save::SaveData;
+use crate::shop::ShopState;
+use crate::{player_transform, AppState, Player, StaticEntities, KEY_INTERACT, TILE_GOALSIZE};
+
+const LOOT_SOURCE: &str = include_str!("../assets/data/loot.ron");
+
+const CHEST_CLOSED: &str = "textures/rpg/props/generic-rpg-treasure-closed.png";
+const CHEST_OPEN: &str = "textures/rpg/props/generic-rpg-trasure-open.png";
+
+// the open sprite is 6px taller than the closed one, keep the bottom edge in place.
+const CHEST_OPEN_OFFSET: f32 = 3.0 * CHEST_SCALE;
+const CHEST_SCALE: f32 = 1.5;
+// how close (center to center) the player has to be to open a chest
+const CHEST_RANGE: f32 = TILE_GOALSIZE * 3.0;
+
+#[derive(Deserialize)]
+pub struct LootTable {
+    // how many entries are drawn per opening
+    pub rolls: u32,
+    pub entries: Vec<LootEntry>,
+}
+
+#[derive(Deserialize)]
+pub struct LootEntry {
+    pub item: ItemKind,
+    // inclusive range of how many items drop
+    pub count: (u32, u32),
+    pub weight: u32,
+}
+
+impl LootTable {
+    pub fn roll(&self, rng: &mut impl Rng) -> Vec<(ItemKind, u32)> {
+        let dist = match WeightedIndex::new(self.entries.iter().map(|entry| entry.weight)) {
+            Ok(dist) => dist,
+            Err(_) => return Vec::new(), // empty table or all weights zero
+        };
+        (0..self.rolls)
+            .map(|_| {
+                let entry = &self.entries[dist.sample(rng)];
+                (entry.item, rng.gen_range(entry.count.0..=entry.count.1))
+            })
+            .collect()
+    }
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+pub struct LootTables(pub HashMap<String, LootTable>);
+
+impl LootTables {
+    pub fn load() -> Self {
+        let mut tables: HashMap<String, LootTable> =
+            ron::de::from_str(LOOT_SOURCE).expect("assets/data/loot.ron is malformed");
+        // a (max, min) count would make rolling panic, take it for the typo it is
+        for (name, table) in tables.iter_mut() {
+            for entry in table.entries.iter_mut() {
+                if entry.count.0 > entry.count.1 {
+                    println!("Loot table '{}': count {:?} of {:?} is reversed", name, entry.count, entry.item);
+                    entry.count = (entry.count.1, entry.count.0);
+                }
+            }
+        }
+        LootTables(tables)
+    }
+}
+
+struct ChestMaterials {
+    closed: Handle<ColorMaterial>,
+    open: Handle<ColorMaterial>,
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+pub struct ChestOpenedEvent {
+    pub id: String,
+}
+
+#[derive(Debug)]
+pub struct Chest {
+    pub id: String,
+    pub loot: String,
+    pub opened: bool,
+}
+
+pub struct ChestPlugin;
+
+impl Plugin for ChestPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(LootTables::load())
+            .add_event::<ChestOpenedEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_chests.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(chest_interact.system().before("dialogue_start")),
+            );
+    }
+}
+
+fn init_chests(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    level: Res<LevelData>,
+    save: Res<SaveData>,
+) {
+    let chest_materials = ChestMaterials {
+        closed: materials.add(asset_server.load(CHEST_CLOSED).into()),
+        open: materials.add(asset_server.load(CHEST_OPEN).into()),
+    };
+
+    for desc in level.chests.iter() {
+        // chests looted in an earlier session stay open and empty
+        let opened = save.opened_chests.contains(&desc.id);
+        let mut translation = level::to_world(desc.pos, 1.0);
+        if opened {
+            translation.y += CHEST_OPEN_OFFSET;
+        }
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                material: if opened {
+                    chest_materials.open.clone()
+                } else {
+                    chest_materials.closed.clone()
+                },
+                transform: Transform {
+                    translation,
+                    scale: Vec3::new(CHEST_SCALE, CHEST_SCALE, 1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Chest {
+                id: desc.id.clone(),
+                loot: desc.loot.clone(),
+                opened,
+            });
+    }
+
+    commands.insert_resource(chest_materials);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn chest_interact(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    chest_materials: Res<ChestMaterials>,
+    loot_tables: Res<LootTables>,
+    static_entities: Res<StaticEntities>,
+    dialogue: Res<DialogueState>,
+    shop: Res<ShopState>,
+    mut save: ResMut<SaveData>,
+    mut opened_events: EventWriter<ChestOpenedEvent>,
+    players: Query<&Transform, With<Player>>,
+    mut chests: Query<(&mut Chest, &mut Transform, &mut Handle<ColorMaterial>), Without<Player>>,
+) {
+    if !keys.just_pressed(KEY_INTERACT) || dialogue.is_open() || shop.is_open() {
+        return;
+    }
+    let player_position = match player_transform(&static_entities, &players) {
+        Some(transform) => transform.translation.truncate(),
+        None => return,
+    };
+
+    let mut rng = rand::thread_rng();
+    for (mut chest, mut transform, mut material) in chests.iter_mut() {
+        if chest.opened
+            || transform.translation.truncate().distance(player_position) > CHEST_RANGE
+        {
+            continue;
+        }
+
+        chest.opened = true;
+        *material = chest_materials.open.clone();
+        transform.translation.y += CHEST_OPEN_OFFSET;
+
+        match loot_tables.0.get(&chest.loot) {
+            Some(table) => {
+                let drops = table.roll(&mut rng);
+                let count = drops.len() as f32;
+                for (index, (kind, amount)) in drops.into_iter().enumerate() {
+                    // fan the drops out in front of the chest
+                    let offset = (index as f32 - (count - 1.0) / 2.0) * TILE_GOALSIZE;
+                    spawn_pickup(
+                        &mut commands,
+                        &asset_server,
+                        &mut materials,
+                        kind,
+                        amount,
+                        transform.translation + Vec3::new(offset, -TILE_GOALSIZE, 0.0),
+                    );
+                }
+            }
+            None => println!("Chest '{}' refers to unknown loot table '{}'", chest.id, chest.loot),
+        }
+
+        save.opened_chests.insert(chest.id.clone());
+        opened_events.send(ChestOpenedEvent { id: chest.id.clone() });
+        // one chest per key press
+        break;
+    }
+}
@@ -0,0 +1,316 @@
+// the player's character controller. turns the held keys into a target velocity and
+// moves the body's velocity towards it with the tuning from assets/data/movement.ron.
+// everything is scaled by the frame time, so the movement feels the same at any frame rate.
+// gravity is applied here as well (the physics world has none), the physics engine
+// only resolves the collisions.
+//
+// jumps are forgiving: a press shortly before landing is remembered (jump buffer), the
+// player can still jump shortly after walking off a ledge (coyote time), and letting go
+// of the key early cuts the jump short.
+//
+// walls: pushing against a wall in the air slides down it slowly, jumping off a wall pushes
+// away from it, and climbable walls (level solids marked climbable) are climbed with W/S.
+//
+// pressing S while running starts a slide, which loses momentum until the player stands up
+// again. the low collider while sliding is handled in slide.rs.
+//
+// S and jump on a platform drops through it (see platform.rs). water and waterfalls
+// (hazard.rs) change the speeds and gravity through the Terrain component.
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use serde::Deserialize;
+
+use crate::dialogue::DialogueState;
+use crate::ground::Contacts;
+use crate::hazard::Terrain;
+use crate::shop::ShopState;
+use crate::{AppState, Player, Timers};
+
+const MOVEMENT_SOURCE: &str = include_str!("../assets/data/movement.ron");
+
+const KEY_LEFT: KeyCode = KeyCode::A;
+const KEY_RIGHT: KeyCode = KeyCode::D;
+const KEY_UP: KeyCode = KeyCode::W;
+const KEY_DOWN: KeyCode = KeyCode::S;
+const KEY_JUMP: KeyCode = KeyCode::LShift;
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+// movement tuning. speeds in pixels per second, accelerations in pixels per second squared.
+#[derive(Deserialize, Default, Debug)]
+pub struct ActionDesc {
+    pub max_speed: f32,
+    // towards max_speed while a direction is held
+    pub acceleration: f32,
+    // towards standing still once the keys are released
+    pub deceleration: f32,
+    // share of acceleration and deceleration left while in the air, 0 to 1
+    pub air_control: f32,
+    // in pixels, with the key held all the way up
+    pub jump_height: f32,
+    // upwards speed is multiplied by this when the key is let go during a jump
+    pub jump_cut: f32,
+    // seconds a jump press is remembered before landing
+    pub jump_buffer: f32,
+    // seconds the player can still jump after leaving the ground
+    pub coyote_time: f32,
+    pub gravity: f32,
+    // gravity multipliers while moving up and down, falling faster than rising feels snappier
+    pub rise_gravity_scale: f32,
+    pub fall_gravity_scale: f32,
+    pub max_fall_speed: f32,
+    // max falling speed while pushing against a wall
+    pub wall_slide_speed: f32,
+    pub climb_speed: f32,
+    // sideways speed of a wall jump, away from the wall
+    pub wall_jump_push: f32,
+    // seconds after a wall jump without sideways control, so the push isn't undone at once
+    pub wall_jump_lock: f32,
+    // speed at the start of a slide
+    pub slide_speed: f32,
+    // deceleration while sliding
+    pub slide_friction: f32,
+    // the slide ends below this speed, unless there is no room to stand up
+    pub slide_min_speed: f32,
+}
+
+impl ActionDesc {
+    pub fn load() -> Self {
+        ron::de::from_str(MOVEMENT_SOURCE).expect("assets/data/movement.ron is malformed")
+    }
+
+    // initial upwards speed reaching jump_height under the rising gravity
+    pub fn jump_speed(&self) -> f32 {
+        (2.0 * self.gravity * self.rise_gravity_scale * self.jump_height).sqrt()
+    }
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+// raised when the player leaves the ground with a jump
+#[derive(Debug, Copy, Clone)]
+pub struct JumpedEvent {
+    pub entity: Entity,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum MoveState {
+    #[default]
+    Ground,
+    Air,
+    // in the air, pushing against a wall
+    WallSlide,
+    // holding on to a climbable wall
+    Climb,
+    Slide,
+}
+
+//////////////////////
+// Components start
+//////////////////////
+
+#[derive(Default, Debug)]
+pub struct Controller {
+    pub state: MoveState,
+    // seconds since the jump key was pressed, while the press is still buffered
+    buffered: Option<f32>,
+    // a jump is under way
+    jumping: bool,
+    // the key has been held since the jump started, letting go cuts the jump short
+    holding: bool,
+    // seconds left without sideways control, after a wall jump or a hit
+    control_lock: f32,
+    // asked to drop through the platform below, platform.rs takes it from here
+    pub drop_through: bool,
+}
+
+impl Controller {
+    // no sideways control for the given seconds
+    pub fn lock_control(&mut self, seconds: f32) {
+        self.control_lock = self.control_lock.max(seconds);
+    }
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct ControllerPlugin;
+
+impl Plugin for ControllerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(ActionDesc::load())
+            .add_event::<JumpedEvent>()
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(player_controller.system().label("controller").after("input")),
+            );
+    }
+}
+
+// moves value towards target by at most step
+fn move_towards(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn player_controller(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    actions: Res<ActionDesc>,
+    timers: Res<Timers>,
+    dialogue: Res<DialogueState>,
+    shop: Res<ShopState>,
+    mut query: Query<
+        (Entity, &mut Controller, &mut Velocity, &Contacts, &Terrain, &mut TextureAtlasSprite),
+        With<Player>,
+    >,
+    mut jumped_events: EventWriter<JumpedEvent>,
+) {
+    let delta = time.delta_seconds();
+    // attacks root the player, and so do open dialogues and shops
+    let attacking = timers.anim_attack_timer.elapsed_secs() > 0.0 && !timers.anim_attack_timer.finished();
+    let blocked = attacking || dialogue.is_open() || shop.is_open();
+
+    let axis = |negative: KeyCode, positive: KeyCode| {
+        let mut value = 0.0;
+        if !blocked && keys.pressed(negative) {
+            value -= 1.0;
+        }
+        if !blocked && keys.pressed(positive) {
+            value += 1.0;
+        }
+        value
+    };
+    let direction = axis(KEY_LEFT, KEY_RIGHT);
+    let vertical = axis(KEY_DOWN, KEY_UP);
+
+    for (entity, mut controller, mut velocity, contacts, terrain, mut sprite) in query.iter_mut() {
+        let max_speed = actions.max_speed * terrain.speed_scale;
+        if keys.just_pressed(KEY_JUMP) && !blocked {
+            controller.buffered = Some(0.0);
+        } else if let Some(age) = controller.buffered {
+            controller.buffered = Some(age + delta).filter(|age| *age <= actions.jump_buffer);
+        }
+        controller.control_lock = (controller.control_lock - delta).max(0.0);
+
+        let mut linear = velocity.linear;
+        if controller.state == MoveState::Slide {
+            // the keys don't steer a slide. under a ceiling it keeps going until there is room
+            let facing = if sprite.flip_x { -1.0 } else { 1.0 };
+            let min_speed = if contacts.under_ceiling() { actions.slide_min_speed } else { 0.0 };
+            linear.x = facing * (linear.x.abs() - actions.slide_friction * delta).max(min_speed);
+        } else if controller.control_lock <= 0.0 {
+            if direction != 0.0 {
+                // the ninja sprite faces right unless flipped
+                sprite.flip_x = direction < 0.0;
+            }
+            let rate = if direction != 0.0 {
+                actions.acceleration
+            } else {
+                actions.deceleration
+            };
+            let control = if contacts.grounded() { 1.0 } else { actions.air_control };
+            linear.x = move_towards(linear.x, direction * max_speed, rate * control * delta);
+        }
+
+        let climbable = contacts.climbable_wall();
+        let wall = contacts.wall();
+        let can_stand = !contacts.under_ceiling();
+        controller.state = match controller.state {
+            MoveState::Slide if contacts.grounded() && (linear.x.abs() > actions.slide_min_speed || !can_stand) => {
+                MoveState::Slide
+            }
+            // only from a run
+            _ if contacts.grounded()
+                && keys.just_pressed(KEY_DOWN)
+                && direction != 0.0
+                && linear.x.abs() >= actions.max_speed * 0.5 =>
+            {
+                linear.x = direction * actions.slide_speed;
+                MoveState::Slide
+            }
+            // let go by stepping down onto the ground, or off the end of the wall
+            MoveState::Climb if climbable.is_some() && !(contacts.grounded() && vertical < 0.0) => MoveState::Climb,
+            _ if climbable.is_some() && vertical > 0.0 => MoveState::Climb,
+            _ if contacts.grounded() => MoveState::Ground,
+            _ if wall.is_some() && wall == Some(direction) && linear.y <= 0.0 => MoveState::WallSlide,
+            _ => MoveState::Air,
+        };
+        let on_wall = matches!(controller.state, MoveState::WallSlide | MoveState::Climb);
+        if on_wall {
+            // face the wall
+            sprite.flip_x = wall == Some(-1.0);
+        }
+
+        if contacts.grounded() && linear.y <= 0.0 {
+            controller.jumping = false;
+        }
+        if controller.buffered.is_some() && vertical < 0.0 && contacts.on_platform_only() {
+            controller.buffered = None;
+            controller.drop_through = true;
+        }
+        let jump = controller.buffered.is_some()
+            && (on_wall || (!controller.jumping && contacts.coyote(actions.coyote_time)))
+            && (controller.state != MoveState::Slide || can_stand);
+        if jump {
+            controller.buffered = None;
+            controller.jumping = true;
+            controller.holding = true;
+            linear.y = actions.jump_speed() * terrain.speed_scale;
+            if on_wall && !contacts.grounded() {
+                // push away from the wall
+                let away = -wall.unwrap_or(0.0);
+                linear.x = away * actions.wall_jump_push;
+                sprite.flip_x = away < 0.0;
+                controller.lock_control(actions.wall_jump_lock);
+            }
+            if controller.state != MoveState::Ground {
+                controller.state = MoveState::Air;
+            }
+            jumped_events.send(JumpedEvent { entity });
+        } else if controller.state == MoveState::Climb {
+            // no gravity on the wall, only let go sideways when moving away from it
+            linear.y = vertical * actions.climb_speed;
+            if Some(direction) != climbable.map(|side| -side) {
+                linear.x = 0.0;
+            }
+        } else {
+            if controller.holding && !keys.pressed(KEY_JUMP) {
+                controller.holding = false;
+                if controller.jumping && linear.y > 0.0 {
+                    linear.y *= actions.jump_cut;
+                }
+            }
+            let scale = if linear.y > 0.0 {
+                actions.rise_gravity_scale
+            } else {
+                actions.fall_gravity_scale
+            };
+            let mut max_fall_speed = if controller.state == MoveState::WallSlide {
+                actions.wall_slide_speed
+            } else {
+                actions.max_fall_speed
+            };
+            if let Some(terrain_max) = terrain.max_fall_speed {
+                max_fall_speed = max_fall_speed.min(terrain_max);
+            }
+            let gravity = actions.gravity * scale * terrain.gravity_scale + terrain.push;
+            linear.y = (linear.y - gravity * delta).max(-max_fall_speed);
+        }
+
+        // only touch the component on change, heron syncs it into the physics world
+        if linear != velocity.linear {
+            velocity.linear = linear;
+        }
+    }
+}
@@ -0,0 +1,328 @@
+// creatures (mobs) placed in the level, their health and the damage they take.
+// the player hits creatures in front of them with the attack key. attacks in the air are
+// in air_attack.rs. creatures hit the foe they target (faction.rs) once it is in reach.
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::Animator;
+use crate::controller::{Controller, MoveState};
+use crate::dialogue::DialogueState;
+use crate::faction::{FactionRules, Target};
+use crate::hurt::{AttackerKind, HitTuning, HurtEvent, Invincibility};
+use crate::level::{self, LevelData};
+use crate::shop::ShopState;
+use crate::{
+    Allegiance, AttackPoints, AppState, Creature, Health, Layer, Player, StaticEntities, StaticEntityId, TILE_GOALSIZE,
+};
+
+const CREATURE_SCALE: f32 = 1.5;
+// reach of the player's sword, measured from the player's center
+const PLAYER_ATTACK_RANGE: f32 = TILE_GOALSIZE * 3.0;
+pub const PLAYER_ATTACK_KEY: KeyCode = KeyCode::Space;
+// reach of a creature's attack, measured between the centers
+const CREATURE_ATTACK_RANGE: f32 = TILE_GOALSIZE * 2.5;
+// seconds between a creature's attacks
+const CREATURE_ATTACK_INTERVAL: f32 = 1.0;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CreatureKind {
+    Slime,
+    Kobold,
+    Fox,
+}
+
+impl CreatureKind {
+    pub fn texture_path(self) -> &'static str {
+        match self {
+            CreatureKind::Slime => "textures/rpg/mobs/slime-green.png",
+            CreatureKind::Kobold => "textures/rpg/mobs/kobold-idle.png",
+            CreatureKind::Fox => "textures/rpg/mobs/fox-run.png",
+        }
+    }
+
+    // clip in assets/data/animations.ron, played on the sheet
+    fn idle_clip(self) -> &'static str {
+        match self {
+            CreatureKind::Slime => "slime/idle",
+            CreatureKind::Kobold => "kobold/idle",
+            CreatureKind::Fox => "fox/idle",
+        }
+    }
+
+    // (frame size, frames in the sheet)
+    fn sheet(self) -> (Vec2, usize) {
+        match self {
+            CreatureKind::Slime => (Vec2::new(16.0, 24.0), 4),
+            CreatureKind::Kobold => (Vec2::new(24.0, 24.0), 15),
+            CreatureKind::Fox => (Vec2::new(24.0, 24.0), 6),
+        }
+    }
+
+    pub fn max_health(self) -> f32 {
+        match self {
+            CreatureKind::Slime => 3.0,
+            CreatureKind::Kobold => 5.0,
+            CreatureKind::Fox => 4.0,
+        }
+    }
+
+    // faction, unless the level data says otherwise
+    pub fn allegiance(self) -> Allegiance {
+        match self {
+            CreatureKind::Slime | CreatureKind::Fox => Allegiance::Red,
+            CreatureKind::Kobold => Allegiance::Yellow,
+        }
+    }
+
+    pub fn attack_points(self) -> f32 {
+        match self {
+            CreatureKind::Slime => 5.0,
+            CreatureKind::Kobold => 10.0,
+            CreatureKind::Fox => 8.0,
+        }
+    }
+}
+
+// raised for every hit, whoever dealt it
+#[derive(Debug, Copy, Clone)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub attacker: AttackerKind,
+    // of the attacker
+    pub allegiance: Allegiance,
+    // where the hit came from, none for hits without a side
+    pub source: Option<Vec2>,
+}
+
+// raised once when a creature's health drops to zero (right before it is despawned)
+#[derive(Debug, Copy, Clone)]
+pub struct CreatureDefeatedEvent {
+    pub kind: CreatureKind,
+    // false when another creature dealt the last hit
+    pub by_player: bool,
+}
+
+// raised once when the player's health drops to zero, spawn.rs brings them back
+#[derive(Debug, Copy, Clone)]
+pub struct PlayerDefeatedEvent {
+    pub player: Entity,
+}
+
+//////////////////////
+// Components start
+//////////////////////
+
+#[derive(Debug)]
+pub struct CreatureInfo {
+    pub kind: CreatureKind,
+}
+
+#[derive(Debug)]
+struct CreatureAttack {
+    cooldown: Timer,
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct CreaturePlugin;
+
+impl Plugin for CreaturePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<DamageEvent>()
+            .add_event::<CreatureDefeatedEvent>()
+            .add_event::<PlayerDefeatedEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_creatures.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(player_attack.system().label("player_attack"))
+                    .with_system(creature_attack.system().label("creature_attack").after("targeting"))
+                    .with_system(damage_handler.system().label("damage").after("player_attack").after("creature_attack")),
+            );
+    }
+}
+
+fn init_creatures(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    level: Res<LevelData>,
+) {
+    for desc in level.creatures.iter() {
+        let (frame_size, frames) = desc.kind.sheet();
+        let allegiance = desc.allegiance.unwrap_or_else(|| desc.kind.allegiance());
+        let atlas = TextureAtlas::from_grid(
+            asset_server.load(desc.kind.texture_path()),
+            frame_size,
+            frames,
+            1,
+        );
+
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: texture_atlases.add(atlas),
+                transform: Transform {
+                    translation: level::to_world(desc.pos, 1.0),
+                    scale: Vec3::new(CREATURE_SCALE, CREATURE_SCALE, 1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Creature)
+            .insert(CreatureInfo { kind: desc.kind })
+            .insert(Health(desc.kind.max_health()))
+            .insert(AttackPoints(desc.kind.attack_points()))
+            .insert(allegiance)
+            .insert(Target::default())
+            .insert(CreatureAttack {
+                cooldown: Timer::from_seconds(CREATURE_ATTACK_INTERVAL, false),
+            })
+            .insert(Animator::new(desc.kind.idle_clip()))
+            .insert(RigidBody::Static)
+            .insert(Layer::of_creature(allegiance).collision_layers())
+            .insert(CollisionShape::Cuboid {
+                half_extends: Vec3::new(frame_size.x / 2.0, frame_size.y / 2.0, 1.0),
+                border_radius: None,
+            });
+    }
+}
+
+// the attack animation itself is started in player_input, this applies the hit.
+#[allow(clippy::type_complexity)]
+fn player_attack(
+    keys: Res<Input<KeyCode>>,
+    static_entities: Res<StaticEntities>,
+    dialogue: Res<DialogueState>,
+    shop: Res<ShopState>,
+    players: Query<(&Transform, &TextureAtlasSprite, &AttackPoints, &Allegiance, &Controller), With<Player>>,
+    creatures: Query<(Entity, &Transform), (With<Creature>, Without<Player>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    if !keys.just_pressed(PLAYER_ATTACK_KEY) || dialogue.is_open() || shop.is_open() {
+        return;
+    }
+    let (player_transform, player_sprite, attack_points, allegiance, controller) = match static_entities
+        .handles
+        .get(&StaticEntityId::Player)
+        .and_then(|player| players.get(*player).ok())
+    {
+        Some(player) => player,
+        None => return,
+    };
+    // only on the ground
+    if controller.state != MoveState::Ground {
+        return;
+    }
+    // the ninja sprite faces right unless flipped
+    let facing = if player_sprite.flip_x { -1.0 } else { 1.0 };
+    let player_position = player_transform.translation.truncate();
+
+    for (entity, transform) in creatures.iter() {
+        let offset = transform.translation.truncate() - player_position;
+        if offset.length() <= PLAYER_ATTACK_RANGE && offset.x * facing >= 0.0 {
+            damage_events.send(DamageEvent {
+                target: entity,
+                amount: attack_points.0,
+                attacker: AttackerKind::Player,
+                allegiance: *allegiance,
+                source: Some(player_position),
+            });
+        }
+    }
+}
+
+// creatures hit their target once it is in reach, every so often
+#[allow(clippy::type_complexity)]
+fn creature_attack(
+    time: Res<Time>,
+    mut creatures: Query<(&Target, &mut CreatureAttack, &CreatureInfo, &AttackPoints, &Allegiance, &Transform)>,
+    targets: Query<&Transform>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (target, mut attack, info, attack_points, allegiance, transform) in creatures.iter_mut() {
+        attack.cooldown.tick(time.delta());
+        let (target, target_transform) = match target.0.and_then(|target| Some((target, targets.get(target).ok()?))) {
+            Some(target) => target,
+            None => continue,
+        };
+        let position = transform.translation.truncate();
+        if !attack.cooldown.finished()
+            || target_transform.translation.truncate().distance(position) > CREATURE_ATTACK_RANGE
+        {
+            continue;
+        }
+        attack.cooldown.reset();
+        damage_events.send(DamageEvent {
+            target,
+            amount: attack_points.0,
+            attacker: AttackerKind::Creature(info.kind),
+            allegiance: *allegiance,
+            source: Some(position),
+        });
+    }
+}
+
+// hits on targets with Invincibility (see hurt.rs) are ignored while it runs, and so are
+// hits between friends unless friendly fire is on (faction.rs)
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn damage_handler(
+    mut commands: Commands,
+    tuning: Res<HitTuning>,
+    rules: Res<FactionRules>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut defeated_events: EventWriter<CreatureDefeatedEvent>,
+    mut player_defeated_events: EventWriter<PlayerDefeatedEvent>,
+    mut hurt_events: EventWriter<HurtEvent>,
+    mut query: Query<(
+        &mut Health,
+        Option<&CreatureInfo>,
+        Option<&Allegiance>,
+        Option<&mut Invincibility>,
+        Option<&Player>,
+    )>,
+) {
+    for event in damage_events.iter() {
+        if let Ok((mut health, info, allegiance, invincibility, player)) = query.get_mut(event.target) {
+            if health.0 <= 0.0 {
+                // already defeated by an earlier hit this frame. the player gets their health
+                // back when respawning (spawn.rs).
+                continue;
+            }
+            if let Some(allegiance) = allegiance {
+                if !rules.can_hurt(event.allegiance, *allegiance) {
+                    continue;
+                }
+            }
+            if let Some(mut invincibility) = invincibility {
+                if invincibility.active() {
+                    continue;
+                }
+                invincibility.start(tuning.get(event.attacker).invincibility);
+            }
+            health.0 -= event.amount;
+            hurt_events.send(HurtEvent {
+                target: event.target,
+                attacker: event.attacker,
+                source: event.source,
+            });
+            if health.0 <= 0.0 {
+                if let Some(info) = info {
+                    defeated_events.send(CreatureDefeatedEvent {
+                        kind: info.kind,
+                        by_player: matches!(event.attacker, AttackerKind::Player | AttackerKind::Kunai),
+                    });
+                    commands.entity(event.target).despawn();
+                } else if player.is_some() {
+                    player_defeated_events.send(PlayerDefeatedEvent { player: event.target });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,436 @@
+// dialogue trees (assets/data/dialogue/*.ron) shown in the text box at the bottom of the screen.
+// while a dialogue is open the player can't move, attack or interact with anything else.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::npc::NpcKind;
+use crate::{AppState, KEY_INTERACT};
+
+// every tree is compiled in, keyed by the name npcs refer to in level.ron.
+const DIALOGUE_SOURCES: &[(&str, &str)] = &[
+    ("gabe", include_str!("../assets/data/dialogue/gabe.ron")),
+    ("hat_guy", include_str!("../assets/data/dialogue/hat_guy.ron")),
+    ("mani", include_str!("../assets/data/dialogue/mani.ron")),
+    ("sensei", include_str!("../assets/data/dialogue/sensei.ron")),
+    ("vendor", include_str!("../assets/data/dialogue/vendor.ron")),
+];
+
+const TEXT_BOX: &str = "textures/rpg/ui/generic-rpg-ui-text-box.png";
+// the text box texture is 112x32, drawn at 4x
+const TEXT_BOX_WIDTH: f32 = 448.0;
+const TEXT_BOX_HEIGHT: f32 = 128.0;
+const TEXT_BOX_PADDING: f32 = 14.0;
+
+const CHARS_PER_SECOND: f32 = 40.0;
+const TEXT_COLOR: Color = Color::rgb(0.25, 0.15, 0.1);
+const CHOICE_COLOR: Color = Color::rgb(0.45, 0.35, 0.3);
+const CHOICE_SELECTED_COLOR: Color = Color::rgb(0.75, 0.2, 0.1);
+
+#[derive(Deserialize)]
+pub struct DialogueTree {
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+#[derive(Deserialize)]
+pub struct DialogueNode {
+    #[serde(default)]
+    pub speaker: Option<String>,
+    pub text: String,
+    // shown below the text, picked with up/down and confirmed with the interact key
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    // followed when there are no choices. None ends the dialogue.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    #[serde(default)]
+    pub next: Option<String>,
+    // something for other systems to do once the choice is confirmed
+    #[serde(default)]
+    pub action: Option<DialogueAction>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum DialogueAction {
+    // open the shop with this name (assets/data/shop.ron)
+    OpenShop(String),
+    // hand out the quest with this id (assets/data/quests.ron)
+    StartQuest(String),
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+pub struct DialogueTrees(pub HashMap<String, DialogueTree>);
+
+impl DialogueTrees {
+    pub fn load() -> Self {
+        let mut trees = HashMap::new();
+        for (name, source) in DIALOGUE_SOURCES.iter() {
+            let tree: DialogueTree = ron::de::from_str(source)
+                .unwrap_or_else(|e| panic!("assets/data/dialogue/{}.ron is malformed: {}", name, e));
+            trees.insert(name.to_string(), tree);
+        }
+        DialogueTrees(trees)
+    }
+}
+
+pub struct ActiveDialogue {
+    pub tree: String,
+    pub node: String,
+    pub npc: NpcKind,
+    // typewriter progress (characters of the node text revealed so far)
+    shown: f32,
+    selected: usize,
+    // skip input in the frame the dialogue was opened, the same key press opened it
+    just_opened: bool,
+}
+
+#[derive(Default)]
+pub struct DialogueState {
+    pub active: Option<ActiveDialogue>,
+}
+
+impl DialogueState {
+    pub fn is_open(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+struct DialogueFont(Handle<Font>);
+
+////////////////////
+// Resources end //
+////////////////////
+
+// ask the dialogue system to open a tree (sent by npcs)
+pub struct StartDialogueEvent {
+    pub tree: String,
+    pub npc: NpcKind,
+}
+
+// raised by the dialogue system, for quests and the like
+#[derive(Debug, Clone)]
+pub enum DialogueEvent {
+    Started { tree: String, npc: NpcKind },
+    // a node has been shown
+    Node { tree: String, node: String },
+    // a choice has been confirmed (index into the node's choices)
+    Choice { tree: String, node: String, choice: usize },
+    // the confirmed choice carried an action
+    Action { tree: String, action: DialogueAction },
+    Ended { tree: String, npc: NpcKind },
+}
+
+// all nodes of the dialogue box (toggled together)
+#[derive(Debug)]
+struct DialogueUi;
+#[derive(Debug)]
+struct DialogueText;
+#[derive(Debug)]
+struct DialogueChoicesText;
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(DialogueTrees::load())
+            .init_resource::<DialogueState>()
+            .add_event::<StartDialogueEvent>()
+            .add_event::<DialogueEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_dialogue_ui.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(dialogue_start.system().label("dialogue_start"))
+                    .with_system(dialogue_input.system().label("dialogue_input").after("dialogue_start"))
+                    .with_system(dialogue_ui_update.system().after("dialogue_input")),
+            );
+    }
+}
+
+fn init_dialogue_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let hidden = Visible {
+        is_visible: false,
+        is_transparent: true,
+    };
+
+    // full width row at the bottom, centering the text box
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Px(TEXT_BOX_HEIGHT)),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(0.0),
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            visible: hidden.clone(),
+            ..Default::default()
+        })
+        .insert(DialogueUi)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(ImageBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(TEXT_BOX_WIDTH), Val::Px(TEXT_BOX_HEIGHT)),
+                        padding: Rect::all(Val::Px(TEXT_BOX_PADDING)),
+                        // ui columns grow upwards, reverse to lay out text top to bottom
+                        flex_direction: FlexDirection::ColumnReverse,
+                        justify_content: JustifyContent::FlexStart,
+                        ..Default::default()
+                    },
+                    material: materials.add(asset_server.load(TEXT_BOX).into()),
+                    visible: hidden.clone(),
+                    ..Default::default()
+                })
+                .insert(DialogueUi)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            style: Style {
+                                max_size: Size::new(
+                                    Val::Px(TEXT_BOX_WIDTH - 2.0 * TEXT_BOX_PADDING),
+                                    Val::Undefined,
+                                ),
+                                ..Default::default()
+                            },
+                            text: Text::with_section(
+                                "",
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 18.0,
+                                    color: TEXT_COLOR,
+                                },
+                                Default::default(),
+                            ),
+                            visible: hidden.clone(),
+                            ..Default::default()
+                        })
+                        .insert(DialogueUi)
+                        .insert(DialogueText);
+                    parent
+                        .spawn_bundle(TextBundle {
+                            style: Style {
+                                margin: Rect {
+                                    top: Val::Px(6.0),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            text: Text::default(),
+                            visible: hidden.clone(),
+                            ..Default::default()
+                        })
+                        .insert(DialogueUi)
+                        .insert(DialogueChoicesText);
+                });
+        });
+
+    commands.insert_resource(DialogueFont(font));
+}
+
+fn dialogue_start(
+    mut requests: EventReader<StartDialogueEvent>,
+    mut events: EventWriter<DialogueEvent>,
+    mut state: ResMut<DialogueState>,
+    trees: Res<DialogueTrees>,
+) {
+    for request in requests.iter() {
+        if state.is_open() {
+            continue;
+        }
+        let tree = match trees.0.get(&request.tree) {
+            Some(tree) => tree,
+            None => {
+                println!("Unknown dialogue tree '{}'", request.tree);
+                continue;
+            }
+        };
+        state.active = Some(ActiveDialogue {
+            tree: request.tree.clone(),
+            node: tree.start.clone(),
+            npc: request.npc,
+            shown: 0.0,
+            selected: 0,
+            just_opened: true,
+        });
+        events.send(DialogueEvent::Started {
+            tree: request.tree.clone(),
+            npc: request.npc,
+        });
+        events.send(DialogueEvent::Node {
+            tree: request.tree.clone(),
+            node: tree.start.clone(),
+        });
+    }
+}
+
+fn dialogue_input(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    trees: Res<DialogueTrees>,
+    mut state: ResMut<DialogueState>,
+    mut events: EventWriter<DialogueEvent>,
+) {
+    if !state.is_open() {
+        return;
+    }
+    let active = match state.active.as_mut() {
+        Some(active) => active,
+        None => return,
+    };
+    let node = match trees.0.get(&active.tree).and_then(|tree| tree.nodes.get(&active.node)) {
+        Some(node) => node,
+        None => {
+            println!("Dialogue '{}' has no node '{}'", active.tree, active.node);
+            let (tree, npc) = (active.tree.clone(), active.npc);
+            state.active = None;
+            events.send(DialogueEvent::Ended { tree, npc });
+            return;
+        }
+    };
+
+    let text_len = node.text.chars().count() as f32;
+    active.shown = (active.shown + CHARS_PER_SECOND * time.delta_seconds()).min(text_len);
+
+    if active.just_opened {
+        active.just_opened = false;
+        return;
+    }
+
+    if !node.choices.is_empty() {
+        if keys.just_pressed(KeyCode::Up) || keys.just_pressed(KeyCode::W) {
+            active.selected = (active.selected + node.choices.len() - 1) % node.choices.len();
+        }
+        if keys.just_pressed(KeyCode::Down) || keys.just_pressed(KeyCode::S) {
+            active.selected = (active.selected + 1) % node.choices.len();
+        }
+    }
+
+    if !(keys.just_pressed(KEY_INTERACT) || keys.just_pressed(KeyCode::Return)) {
+        return;
+    }
+    // first press finishes the typewriter, second one continues
+    if active.shown < text_len {
+        active.shown = text_len;
+        return;
+    }
+
+    let next = if node.choices.is_empty() {
+        node.next.clone()
+    } else {
+        events.send(DialogueEvent::Choice {
+            tree: active.tree.clone(),
+            node: active.node.clone(),
+            choice: active.selected,
+        });
+        let choice = &node.choices[active.selected];
+        if let Some(action) = choice.action.clone() {
+            events.send(DialogueEvent::Action {
+                tree: active.tree.clone(),
+                action,
+            });
+        }
+        choice.next.clone()
+    };
+
+    match next {
+        Some(next) => {
+            active.node = next;
+            active.shown = 0.0;
+            active.selected = 0;
+            events.send(DialogueEvent::Node {
+                tree: active.tree.clone(),
+                node: active.node.clone(),
+            });
+        }
+        None => {
+            let (tree, npc) = (active.tree.clone(), active.npc);
+            state.active = None;
+            events.send(DialogueEvent::Ended { tree, npc });
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn dialogue_ui_update(
+    state: Res<DialogueState>,
+    trees: Res<DialogueTrees>,
+    font: Res<DialogueFont>,
+    mut visibles: Query<&mut Visible, With<DialogueUi>>,
+    mut texts: QuerySet<(
+        Query<&mut Text, With<DialogueText>>,
+        Query<&mut Text, With<DialogueChoicesText>>,
+    )>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for mut visible in visibles.iter_mut() {
+        visible.is_visible = state.is_open();
+    }
+
+    let active = match state.active.as_ref() {
+        Some(active) => active,
+        None => return,
+    };
+    let node = match trees.0.get(&active.tree).and_then(|tree| tree.nodes.get(&active.node)) {
+        Some(node) => node,
+        None => return,
+    };
+
+    let shown: String = node.text.chars().take(active.shown as usize).collect();
+    for mut text in texts.q0_mut().iter_mut() {
+        text.sections[0].value = match node.speaker.as_ref() {
+            Some(speaker) => format!("{}: {}", speaker, shown),
+            None => shown.clone(),
+        };
+    }
+
+    // choices only show up once the text is fully typed out
+    let typed_out = active.shown as usize >= node.text.chars().count();
+    for mut text in texts.q1_mut().iter_mut() {
+        text.sections = if typed_out {
+            node.choices
+                .iter()
+                .enumerate()
+                .map(|(index, choice)| TextSection {
+                    value: format!("{} {}\n", if index == active.selected { ">" } else { " " }, choice.text),
+                    style: TextStyle {
+                        font: font.0.clone(),
+                        font_size: 16.0,
+                        color: if index == active.selected {
+                            CHOICE_SELECTED_COLOR
+                        } else {
+                            CHOICE_COLOR
+                        },
+                    },
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+}
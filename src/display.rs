@@ -0,0 +1,213 @@
+// resolution independence. the world is laid out for a logical view of WIN_WIDTH x WIN_HEIGHT
+// and the game camera scales that view to whatever size the window has, the modes are
+// described in assets/data/display.ron. the hud is moved into a frame covering the logical
+// view, so it lays out inside it, and bars cover the rest of the window.
+
+use bevy::prelude::*;
+use bevy::render::camera::{Camera, CameraProjection, OrthographicProjection, ScalingMode};
+use serde::Deserialize;
+
+use crate::{AppState, Camera2d, WinSize, WIN_HEIGHT, WIN_WIDTH};
+
+const DISPLAY_SOURCE: &str = include_str!("../assets/data/display.ron");
+
+const KEY_SCALE_MODE: KeyCode = KeyCode::F9;
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScaleMode {
+    Integer,
+    Letterbox,
+    Fit,
+}
+
+impl ScaleMode {
+    fn next(self) -> Self {
+        match self {
+            ScaleMode::Integer => ScaleMode::Letterbox,
+            ScaleMode::Letterbox => ScaleMode::Fit,
+            ScaleMode::Fit => ScaleMode::Integer,
+        }
+    }
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Deserialize, Debug)]
+struct DisplaySettings {
+    mode: ScaleMode,
+    bar_color: (f32, f32, f32),
+    // window size the camera and the hud were laid out for, none to lay them out again
+    #[serde(skip)]
+    laid_out: Option<Vec2>,
+}
+
+impl DisplaySettings {
+    fn load() -> Self {
+        ron::de::from_str(DISPLAY_SOURCE).expect("assets/data/display.ron is malformed")
+    }
+
+    // window pixels per logical unit, none when stretched
+    fn scale(&self, window: Vec2) -> Option<f32> {
+        let fit = (window.x / WIN_WIDTH).min(window.y / WIN_HEIGHT);
+        match self.mode {
+            // windows smaller than the view can't do whole multiples
+            ScaleMode::Integer if fit >= 1.0 => Some(fit.floor()),
+            ScaleMode::Integer | ScaleMode::Letterbox => Some(fit),
+            ScaleMode::Fit => None,
+        }
+    }
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+//////////////////////
+// Components start
+//////////////////////
+
+// ui node covering the logical view, the hud's roots are moved into it
+struct HudFrame;
+
+// covers the window outside the logical view
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Bar {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(DisplaySettings::load())
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_frame.system()),
+            )
+            // windows can be resized in every state
+            .add_system(scale_mode_toggle.system().label("scale_mode"))
+            .add_system(viewport_handler.system().after("scale_mode"))
+            .add_system(hud_adopt.system());
+    }
+}
+
+fn init_frame(mut commands: Commands, display: Res<DisplaySettings>, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let absolute = Style {
+        position_type: PositionType::Absolute,
+        ..Default::default()
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: absolute.clone(),
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .insert(HudFrame);
+
+    let (r, g, b) = display.bar_color;
+    let material = materials.add(Color::rgb(r, g, b).into());
+    for bar in [Bar::Left, Bar::Right, Bar::Top, Bar::Bottom].iter() {
+        commands
+            .spawn_bundle(NodeBundle {
+                style: absolute.clone(),
+                material: material.clone(),
+                ..Default::default()
+            })
+            .insert(*bar);
+    }
+}
+
+fn scale_mode_toggle(keys: Res<Input<KeyCode>>, mut display: ResMut<DisplaySettings>) {
+    if keys.just_pressed(KEY_SCALE_MODE) {
+        display.mode = display.mode.next();
+        display.laid_out = None;
+        println!("Scale mode {:?}", display.mode);
+    }
+}
+
+// lays the camera, the hud frame and the bars out again whenever the window size or the
+// mode changes
+#[allow(clippy::type_complexity)]
+fn viewport_handler(
+    windows: Res<Windows>,
+    mut display: ResMut<DisplaySettings>,
+    mut win_size: Option<ResMut<WinSize>>,
+    mut cameras: Query<(&mut OrthographicProjection, &mut Camera), With<Camera2d>>,
+    mut nodes: Query<(&mut Style, Option<&Bar>), Or<(With<HudFrame>, With<Bar>)>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => Vec2::new(window.width(), window.height()),
+        None => return,
+    };
+    // the camera and the hud are set up with the level
+    if display.laid_out == Some(window) || cameras.iter_mut().next().is_none() || nodes.iter_mut().next().is_none() {
+        return;
+    }
+    display.laid_out = Some(window);
+    if let Some(win_size) = win_size.as_mut() {
+        win_size.w = window.x;
+        win_size.h = window.y;
+    }
+
+    let logical = Vec2::new(WIN_WIDTH, WIN_HEIGHT);
+    // half of what the camera shows at zoom 1, and the logical view's size in the window
+    let (extents, size) = match display.scale(window) {
+        Some(scale) => (window / scale / 2.0, logical * scale),
+        None => (logical / 2.0, window),
+    };
+    for (mut projection, mut camera) in cameras.iter_mut() {
+        // bevy no longer sizes the projection after the window
+        projection.scaling_mode = ScalingMode::None;
+        projection.left = -extents.x;
+        projection.right = extents.x;
+        projection.bottom = -extents.y;
+        projection.top = extents.y;
+        camera.projection_matrix = projection.get_projection_matrix();
+    }
+
+    // the view is centered, so the bars on opposite sides are the same size
+    let margin = (window - size) / 2.0;
+    for (mut style, bar) in nodes.iter_mut() {
+        // bottom left corner and size
+        let (corner, node_size) = match bar {
+            None => (margin, size),
+            Some(Bar::Left) => (Vec2::ZERO, Vec2::new(margin.x, window.y)),
+            Some(Bar::Right) => (Vec2::new(window.x - margin.x, 0.0), Vec2::new(margin.x, window.y)),
+            Some(Bar::Bottom) => (Vec2::ZERO, Vec2::new(window.x, margin.y)),
+            Some(Bar::Top) => (Vec2::new(0.0, window.y - margin.y), Vec2::new(window.x, margin.y)),
+        };
+        style.position = Rect {
+            left: Val::Px(corner.x),
+            bottom: Val::Px(corner.y),
+            ..Default::default()
+        };
+        style.size = Size::new(Val::Px(node_size.x), Val::Px(node_size.y));
+    }
+}
+
+// ui roots spawned by the other modules go into the frame, their sizes and positions are
+// then relative to the logical view instead of the window
+#[allow(clippy::type_complexity)]
+fn hud_adopt(
+    mut commands: Commands,
+    frames: Query<Entity, With<HudFrame>>,
+    roots: Query<Entity, (With<Node>, Without<Parent>, Without<HudFrame>, Without<Bar>)>,
+) {
+    let frame = match frames.iter().next() {
+        Some(frame) => frame,
+        None => return,
+    };
+    let roots: Vec<Entity> = roots.iter().collect();
+    if !roots.is_empty() {
+        commands.entity(frame).push_children(&roots);
+    }
+}
@@ -0,0 +1,86 @@
+// friend and foe. the player, creatures, projectiles and structures carry an Allegiance.
+// members of the same faction don't hurt each other unless friendly fire is on (F8),
+// bodies without a faction (Allegiance::None) are everybody's foe. creatures pick the
+// nearest foe in sight as their target, be it the player or a creature of another faction.
+// creatures of the player's faction are in the Allies physics layer, the player walks
+// through them instead of bumping into them.
+
+use bevy::prelude::*;
+
+use crate::{Allegiance, AppState, Creature, Health};
+
+const KEY_FRIENDLY_FIRE: KeyCode = KeyCode::F8;
+// how far creatures look for foes, in pixels
+const CREATURE_SIGHT: f32 = 72.0;
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Default, Debug)]
+pub struct FactionRules {
+    // hits between members of the same faction count
+    pub friendly_fire: bool,
+}
+
+impl FactionRules {
+    pub fn can_hurt(&self, attacker: Allegiance, target: Allegiance) -> bool {
+        self.friendly_fire || !attacker.friendly_to(target)
+    }
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+//////////////////////
+// Components start
+//////////////////////
+
+// the foe a creature is after
+#[derive(Default, Debug)]
+pub struct Target(pub Option<Entity>);
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct FactionPlugin;
+
+impl Plugin for FactionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<FactionRules>()
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(friendly_fire_toggle.system())
+                    .with_system(creature_targeting.system().label("targeting")),
+            );
+    }
+}
+
+fn friendly_fire_toggle(keys: Res<Input<KeyCode>>, mut rules: ResMut<FactionRules>) {
+    if keys.just_pressed(KEY_FRIENDLY_FIRE) {
+        rules.friendly_fire = !rules.friendly_fire;
+        println!("Friendly fire {}", if rules.friendly_fire { "on" } else { "off" });
+    }
+}
+
+// anything with health and an allegiance can be a target, friends never are
+fn creature_targeting(
+    mut creatures: Query<(Entity, &Allegiance, &Transform, &mut Target), With<Creature>>,
+    bodies: Query<(Entity, &Allegiance, &Transform), With<Health>>,
+) {
+    for (entity, allegiance, transform, mut target) in creatures.iter_mut() {
+        let position = transform.translation.truncate();
+        let nearest = bodies
+            .iter()
+            .filter(|(other, other_allegiance, _)| *other != entity && !allegiance.friendly_to(**other_allegiance))
+            .map(|(other, _, other_transform)| (other, other_transform.translation.truncate().distance(position)))
+            .filter(|(_, distance)| *distance <= CREATURE_SIGHT)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(other, _)| other);
+        if target.0 != nearest {
+            target.0 = nearest;
+        }
+    }
+}
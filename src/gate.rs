@@ -0,0 +1,414 @@
+// gates: sensor zones in the level that lead to pages of the website (home, blog, ...).
+// walking into or out of a gate raises a GateEvent. the gate the player currently stands in
+// is mirrored into GateLink, which the embedding page reads.
+//
+// every gate shows a floating label. inside a gate, the interact key asks for confirmation
+// and pressing it again navigates through the gate. what that means depends on
+// the platform: the GateHandler resource forwards to javascript on wasm, and does whatever
+// assets/data/gates.ron configures for the gate natively (log or open a url).
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use serde::Deserialize;
+
+use crate::level::{self, LevelData};
+use crate::dialogue::DialogueState;
+use crate::shop::ShopState;
+use crate::{collided_with_player, AppState, Layer, StaticEntities, KEY_INTERACT, TILE_UNIT_TRANSLATION};
+
+#[cfg(not(target_arch = "wasm32"))]
+const GATE_TARGETS_SOURCE: &str = include_str!("../assets/data/gates.ron");
+
+const HIGHLIGHT_COLOR: Color = Color::rgba(1.0, 0.9, 0.3, 0.35);
+const LABEL_COLOR: Color = Color::WHITE;
+const PROMPT_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+const LABEL_FONT_SIZE: f32 = 14.0;
+const PROMPT_FONT_SIZE: f32 = 11.0;
+
+// everything in the game that links out to the website.
+#[derive(Deserialize, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum GateIdentifier {
+    None = 3000,
+    Home = 3001,
+    Blog = 3002,
+    Markets = 3003,
+    About = 3004,
+    Portfolio = 3005,
+}
+
+impl GateIdentifier {
+    pub fn all() -> [GateIdentifier; 5] {
+        [
+            GateIdentifier::Home,
+            GateIdentifier::Blog,
+            GateIdentifier::Markets,
+            GateIdentifier::About,
+            GateIdentifier::Portfolio,
+        ]
+    }
+
+    pub fn from_u16(value: u16) -> Self {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|gate| *gate as u16 == value)
+            .unwrap_or(GateIdentifier::None)
+    }
+
+    // shown above the gate, also the name the javascript api uses
+    pub fn label(self) -> &'static str {
+        match self {
+            GateIdentifier::None => "None",
+            GateIdentifier::Home => "Home",
+            GateIdentifier::Blog => "Blog",
+            GateIdentifier::Markets => "Markets",
+            GateIdentifier::About => "About",
+            GateIdentifier::Portfolio => "Portfolio",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Self {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|gate| gate.label() == label)
+            .unwrap_or(GateIdentifier::None)
+    }
+
+    // page of the website the gate leads to
+    pub fn target(self) -> &'static str {
+        match self {
+            GateIdentifier::None => "",
+            GateIdentifier::Home => "/",
+            GateIdentifier::Blog => "/blog",
+            GateIdentifier::Markets => "/markets",
+            GateIdentifier::About => "/about",
+            GateIdentifier::Portfolio => "/portfolio",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GateEvent {
+    Entered(GateIdentifier),
+    Left(GateIdentifier),
+}
+
+// the player asked to go through a gate
+#[derive(Debug, Copy, Clone)]
+pub struct GateNavigateEvent(pub GateIdentifier);
+
+// what a gate does natively, configured per gate in assets/data/gates.ron
+#[derive(Deserialize, Debug, Clone)]
+pub enum GateTarget {
+    Log,
+    OpenUrl(String),
+}
+
+// does the actual navigation. one implementation per platform, picked in GatePlugin.
+pub trait GateHandler: Send + Sync + 'static {
+    fn navigate(&self, gate: GateIdentifier);
+}
+
+// ask for a gate's outline to be shown or hidden (sent from the embedding page)
+#[derive(Debug, Copy, Clone)]
+pub struct HighlightGateEvent {
+    pub gate: GateIdentifier,
+    pub on: bool,
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+// the gate the player is standing in, GateIdentifier::None outside of gates.
+// shared (cloned Arc) with whatever runs outside of the bevy schedule, e.g. javascript.
+// set when a gate is entered, reset when it is left.
+#[derive(Clone)]
+pub struct GateLink(Arc<AtomicU16>);
+
+impl Default for GateLink {
+    fn default() -> Self {
+        GateLink(Arc::new(AtomicU16::new(GateIdentifier::None as u16)))
+    }
+}
+
+impl GateLink {
+    pub fn get(&self) -> GateIdentifier {
+        GateIdentifier::from_u16(self.0.load(Ordering::SeqCst))
+    }
+
+    fn set(&self, gate: GateIdentifier) {
+        self.0.store(gate as u16, Ordering::SeqCst);
+    }
+}
+
+pub struct GateHandlerRes(pub Box<dyn GateHandler>);
+
+// gate waiting for the player to confirm navigating through it
+#[derive(Default)]
+pub struct GateConfirm {
+    pub pending: Option<GateIdentifier>,
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct NativeGateHandler {
+    targets: HashMap<GateIdentifier, GateTarget>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl NativeGateHandler {
+    pub fn load() -> Self {
+        NativeGateHandler {
+            targets: ron::de::from_str(GATE_TARGETS_SOURCE).expect("assets/data/gates.ron is malformed"),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GateHandler for NativeGateHandler {
+    fn navigate(&self, gate: GateIdentifier) {
+        match self.targets.get(&gate).unwrap_or(&GateTarget::Log) {
+            GateTarget::Log => println!("Navigating through gate {:?}", gate),
+            GateTarget::OpenUrl(url) => open_url(url),
+        }
+    }
+}
+
+// hand the url to the desktop's default browser
+#[cfg(not(target_arch = "wasm32"))]
+fn open_url(url: &str) {
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("cmd").args(&["/C", "start", "", url]).spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg(url).spawn();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open").arg(url).spawn();
+
+    if let Err(e) = result {
+        println!("Could not open {}: {}", url, e);
+    }
+}
+
+#[derive(Debug)]
+pub struct Gate(pub GateIdentifier);
+
+// "press E" text floating below a gate's label
+#[derive(Debug)]
+struct GatePromptText(GateIdentifier);
+
+pub struct GatePlugin;
+
+impl Plugin for GatePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let link = GateLink::default();
+        #[cfg(target_arch = "wasm32")]
+        crate::web::register_gate_link(link.clone());
+
+        #[cfg(target_arch = "wasm32")]
+        let handler: Box<dyn GateHandler> = Box::new(crate::web::JsGateHandler);
+        #[cfg(not(target_arch = "wasm32"))]
+        let handler: Box<dyn GateHandler> = Box::new(NativeGateHandler::load());
+
+        app.insert_resource(link)
+            .insert_resource(GateHandlerRes(handler))
+            .init_resource::<GateConfirm>()
+            .add_event::<GateEvent>()
+            .add_event::<GateNavigateEvent>()
+            .add_event::<HighlightGateEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_gates.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(gate_collision.system().label("gate_collision"))
+                    .with_system(gate_link_update.system().label("gate_link").after("gate_collision"))
+                    .with_system(gate_interact.system().label("gate_interact").after("gate_link"))
+                    .with_system(gate_navigation.system().after("gate_interact"))
+                    .with_system(gate_prompt_update.system().after("gate_interact"))
+                    .with_system(gate_highlight.system()),
+            );
+    }
+}
+
+fn init_gates(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    level: Res<LevelData>,
+) {
+    let highlight = materials.add(HIGHLIGHT_COLOR.into());
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let centered = TextAlignment {
+        vertical: VerticalAlign::Center,
+        horizontal: HorizontalAlign::Center,
+    };
+    for desc in level.gates.iter() {
+        let size = Vec2::new(desc.size.0, desc.size.1) * TILE_UNIT_TRANSLATION;
+        // the sprite is only shown while the gate is highlighted
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite::new(size),
+                material: highlight.clone(),
+                transform: Transform::from_translation(level::to_world(desc.pos, 2.0)),
+                visible: Visible {
+                    is_visible: false,
+                    is_transparent: true,
+                },
+                ..Default::default()
+            })
+            .insert(Gate(desc.gate))
+            .insert(RigidBody::Sensor)
+            .insert(Layer::Sensors.collision_layers())
+            .insert(CollisionShape::Cuboid {
+                half_extends: (size / 2.0).extend(1.0),
+                border_radius: None,
+            })
+            .with_children(|parent| {
+                // label above the gate, prompt right below it
+                parent.spawn_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        desc.gate.label(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: LABEL_FONT_SIZE,
+                            color: LABEL_COLOR,
+                        },
+                        centered,
+                    ),
+                    transform: Transform::from_xyz(0.0, size.y / 2.0 + LABEL_FONT_SIZE, 1.0),
+                    ..Default::default()
+                });
+                parent
+                    .spawn_bundle(Text2dBundle {
+                        text: Text::with_section(
+                            "",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: PROMPT_FONT_SIZE,
+                                color: PROMPT_COLOR,
+                            },
+                            centered,
+                        ),
+                        transform: Transform::from_xyz(0.0, size.y / 2.0 + 2.0, 1.0),
+                        visible: Visible {
+                            is_visible: false,
+                            is_transparent: true,
+                        },
+                        ..Default::default()
+                    })
+                    .insert(GatePromptText(desc.gate));
+            });
+    }
+}
+
+fn gate_collision(
+    mut events: EventReader<CollisionEvent>,
+    mut gate_events: EventWriter<GateEvent>,
+    static_entities: Res<StaticEntities>,
+    query: Query<&Gate>,
+) {
+    for event in events.iter() {
+        let (d1, d2, entered) = match event {
+            CollisionEvent::Started(d1, d2) => (d1, d2, true),
+            CollisionEvent::Stopped(d1, d2) => (d1, d2, false),
+        };
+        if let Some(entity) = collided_with_player(d1, d2, &static_entities) {
+            if let Ok(gate) = query.get(entity) {
+                gate_events.send(if entered {
+                    GateEvent::Entered(gate.0)
+                } else {
+                    GateEvent::Left(gate.0)
+                });
+            }
+        }
+    }
+}
+
+fn gate_link_update(mut events: EventReader<GateEvent>, link: Res<GateLink>) {
+    for event in events.iter() {
+        match *event {
+            GateEvent::Entered(gate) => link.set(gate),
+            // gates may overlap, only clear the one that was left
+            GateEvent::Left(gate) => {
+                if link.get() == gate {
+                    link.set(GateIdentifier::None);
+                }
+            }
+        }
+    }
+}
+
+// first press asks, second press goes. escape or walking out of the gate cancels.
+fn gate_interact(
+    keys: Res<Input<KeyCode>>,
+    link: Res<GateLink>,
+    dialogue: Res<DialogueState>,
+    shop: Res<ShopState>,
+    mut confirm: ResMut<GateConfirm>,
+    mut events: EventWriter<GateNavigateEvent>,
+) {
+    let gate = link.get();
+    if confirm.pending.is_some() && (confirm.pending != Some(gate) || keys.just_pressed(KeyCode::Escape)) {
+        confirm.pending = None;
+        return;
+    }
+    if !keys.just_pressed(KEY_INTERACT) || dialogue.is_open() || shop.is_open() || gate == GateIdentifier::None {
+        return;
+    }
+    if confirm.pending == Some(gate) {
+        confirm.pending = None;
+        events.send(GateNavigateEvent(gate));
+    } else {
+        confirm.pending = Some(gate);
+    }
+}
+
+fn gate_prompt_update(
+    link: Res<GateLink>,
+    confirm: Res<GateConfirm>,
+    mut query: Query<(&GatePromptText, &mut Text, &mut Visible)>,
+) {
+    let current = link.get();
+    for (prompt, mut text, mut visible) in query.iter_mut() {
+        let shown = prompt.0 == current;
+        if visible.is_visible != shown {
+            visible.is_visible = shown;
+        }
+        let value = if confirm.pending == Some(prompt.0) {
+            format!("Go to {}?  E: yes  Esc: no", prompt.0.label())
+        } else {
+            "Press E to enter".to_string()
+        };
+        if shown && text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+// the one code path for both platforms, only the handler differs
+fn gate_navigation(mut events: EventReader<GateNavigateEvent>, handler: Res<GateHandlerRes>) {
+    for event in events.iter() {
+        handler.0.navigate(event.0);
+    }
+}
+
+fn gate_highlight(mut events: EventReader<HighlightGateEvent>, mut query: Query<(&Gate, &mut Visible)>) {
+    for event in events.iter() {
+        for (gate, mut visible) in query.iter_mut() {
+            if gate.0 == event.gate {
+                visible.is_visible = event.on;
+            }
+        }
+    }
+}
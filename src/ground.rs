@@ -0,0 +1,214 @@
+// ground and wall contacts of the player. thin sensor shapes at the feet and both sides
+// of the player's collider count the world colliders they overlap, so standing on the
+// ground, touching a wall and bumping into anything else can be told apart.
+// standing on a platform is found out in platform.rs instead.
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use heron::SensorShape;
+
+use crate::platform::Climbable;
+use crate::{AppState, Layer};
+
+// thickness of the sensor shapes, in pixels
+const SENSOR_THICKNESS: f32 = 2.0;
+
+// raised when the player's feet touch the ground after being in the air
+#[derive(Debug, Copy, Clone)]
+pub struct LandedEvent {
+    pub entity: Entity,
+}
+
+//////////////////////
+// Components start
+//////////////////////
+
+// which side of the body a sensor shape (child of the body) sits on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ContactSensor {
+    Foot,
+    WallLeft,
+    WallRight,
+    // room above a sliding body (slide.rs)
+    Head,
+}
+
+// number of world colliders touching each sensor of the body
+#[derive(Debug, Default)]
+pub struct Contacts {
+    ground: u32,
+    wall_left: u32,
+    wall_right: u32,
+    // the part of the wall contacts that can be climbed
+    climbable_left: u32,
+    climbable_right: u32,
+    ceiling: u32,
+    // standing on a one-way or moving platform
+    on_platform: bool,
+    // seconds since the feet last touched the ground
+    pub airborne: f32,
+}
+
+impl Contacts {
+    pub fn grounded(&self) -> bool {
+        self.ground > 0 || self.on_platform
+    }
+
+    // standing on platforms only, with nothing solid under the feet
+    pub fn on_platform_only(&self) -> bool {
+        self.on_platform && self.ground == 0
+    }
+
+    // true if this lands the body
+    pub fn set_on_platform(&mut self, on_platform: bool) -> bool {
+        let was_grounded = self.grounded();
+        self.on_platform = on_platform;
+        !was_grounded && self.grounded()
+    }
+
+    // grounded, or left the ground no longer than coyote_time seconds ago
+    pub fn coyote(&self, coyote_time: f32) -> bool {
+        self.grounded() || self.airborne <= coyote_time
+    }
+
+    // -1.0 for a wall on the left, 1.0 on the right
+    pub fn wall(&self) -> Option<f32> {
+        if self.wall_left > 0 {
+            Some(-1.0)
+        } else if self.wall_right > 0 {
+            Some(1.0)
+        } else {
+            None
+        }
+    }
+
+    // something is in the way of standing up from a slide
+    pub fn under_ceiling(&self) -> bool {
+        self.ceiling > 0
+    }
+
+    // like wall, for climbable walls only
+    pub fn climbable_wall(&self) -> Option<f32> {
+        if self.climbable_left > 0 {
+            Some(-1.0)
+        } else if self.climbable_right > 0 {
+            Some(1.0)
+        } else {
+            None
+        }
+    }
+
+    // a contact of the sensor started (delta 1) or stopped (delta -1)
+    fn count(&mut self, sensor: ContactSensor, climbable: bool, delta: i32) {
+        let counters = match sensor {
+            ContactSensor::Foot => vec![&mut self.ground],
+            ContactSensor::WallLeft if climbable => vec![&mut self.wall_left, &mut self.climbable_left],
+            ContactSensor::WallLeft => vec![&mut self.wall_left],
+            ContactSensor::WallRight if climbable => vec![&mut self.wall_right, &mut self.climbable_right],
+            ContactSensor::WallRight => vec![&mut self.wall_right],
+            ContactSensor::Head => vec![&mut self.ceiling],
+        };
+        for counter in counters {
+            *counter = (*counter as i32 + delta).max(0) as u32;
+        }
+    }
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+// adds the sensors to a body with a cuboid collider of the given half size.
+// heron ignores the body's scale, so sizes and offsets are in pixels.
+pub fn spawn_contact_sensors(parent: &mut ChildBuilder, half_size: Vec2) {
+    let sensors = [
+        (
+            ContactSensor::Foot,
+            Vec2::new(0.0, -half_size.y),
+            Vec2::new(half_size.x * 0.8, SENSOR_THICKNESS),
+        ),
+        // kept clear of the feet, so the ground doesn't count as a wall
+        (
+            ContactSensor::WallLeft,
+            Vec2::new(-half_size.x, half_size.y * 0.1),
+            Vec2::new(SENSOR_THICKNESS, half_size.y * 0.7),
+        ),
+        (
+            ContactSensor::WallRight,
+            Vec2::new(half_size.x, half_size.y * 0.1),
+            Vec2::new(SENSOR_THICKNESS, half_size.y * 0.7),
+        ),
+    ];
+    for (sensor, offset, half_extends) in sensors.iter() {
+        spawn_sensor(parent, *sensor, *offset, *half_extends);
+    }
+}
+
+pub fn spawn_sensor(parent: &mut ChildBuilder, sensor: ContactSensor, offset: Vec2, half_extends: Vec2) {
+    parent
+        .spawn()
+        .insert(Transform::from_translation(offset.extend(0.0)))
+        .insert(GlobalTransform::default())
+        .insert(sensor)
+        .insert(CollisionShape::Cuboid {
+            half_extends: half_extends.extend(1.0),
+            border_radius: None,
+        })
+        .insert(SensorShape)
+        .insert(Layer::ContactSensors.collision_layers());
+}
+
+pub struct GroundPlugin;
+
+impl Plugin for GroundPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<LandedEvent>()
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(contact_sensor_handler.system().label("contacts"))
+                    .with_system(airborne_timer.system().after("contacts")),
+            );
+    }
+}
+
+fn contact_sensor_handler(
+    mut events: EventReader<CollisionEvent>,
+    sensors: Query<(&ContactSensor, &Parent)>,
+    climbables: Query<&Climbable>,
+    mut bodies: Query<&mut Contacts>,
+    mut landed_events: EventWriter<LandedEvent>,
+) {
+    for event in events.iter() {
+        let (d1, d2, started) = match event {
+            CollisionEvent::Started(d1, d2) => (d1, d2, true),
+            CollisionEvent::Stopped(d1, d2) => (d1, d2, false),
+        };
+        for (data, other) in [(d1, d2), (d2, d1)].iter() {
+            if Layer::of(other) != Some(Layer::World) {
+                continue;
+            }
+            let (sensor, parent) = match sensors.get(data.collision_shape_entity()) {
+                Ok(sensor) => sensor,
+                Err(_) => continue,
+            };
+            if let Ok(mut contacts) = bodies.get_mut(parent.0) {
+                let was_grounded = contacts.grounded();
+                let climbable = climbables.get(other.rigid_body_entity()).is_ok();
+                contacts.count(*sensor, climbable, if started { 1 } else { -1 });
+                if !was_grounded && contacts.grounded() {
+                    landed_events.send(LandedEvent { entity: parent.0 });
+                }
+            }
+        }
+    }
+}
+
+fn airborne_timer(time: Res<Time>, mut bodies: Query<&mut Contacts>) {
+    for mut contacts in bodies.iter_mut() {
+        if contacts.grounded() {
+            contacts.airborne = 0.0;
+        } else {
+            contacts.airborne += time.delta_seconds();
+        }
+    }
+}
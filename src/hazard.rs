@@ -0,0 +1,314 @@
+// hazard and terrain volumes from the level data. water slows the player down and lowers
+// gravity, spikes hurt, waterfalls push down. the tuning is in assets/data/hazards.ron,
+// the controller (controller.rs) applies the Terrain effects to the movement.
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use serde::Deserialize;
+
+use crate::creature::DamageEvent;
+use crate::hurt::AttackerKind;
+use crate::level::{self, LevelData};
+use crate::{
+    collided_with_player, Allegiance, AppState, Layer, StaticEntities, StaticEntityId, TILE_ACTUALSIZE, TILE_GOALSIZE,
+    TILE_UNIT_TRANSLATION,
+};
+
+const HAZARDS_SOURCE: &str = include_str!("../assets/data/hazards.ron");
+
+const WATER_COLOR: Color = Color::rgba(0.2, 0.45, 0.85, 0.45);
+const SPIKES_COLOR: Color = Color::rgb(0.55, 0.55, 0.6);
+const WATERFALL_FRAMES: usize = 7;
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HazardKind {
+    Water,
+    Spikes,
+    Waterfall,
+}
+
+#[derive(Deserialize, Debug)]
+struct WaterDesc {
+    speed_scale: f32,
+    gravity_scale: f32,
+    max_fall_speed: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct SpikesDesc {
+    damage: f32,
+    interval: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct WaterfallDesc {
+    push: f32,
+    frame_time: f32,
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Deserialize, Debug)]
+struct HazardTuning {
+    water: WaterDesc,
+    spikes: SpikesDesc,
+    waterfall: WaterfallDesc,
+}
+
+impl HazardTuning {
+    fn load() -> Self {
+        ron::de::from_str(HAZARDS_SOURCE).expect("assets/data/hazards.ron is malformed")
+    }
+}
+
+// materials of the waterfall tile animation
+struct WaterfallFrames {
+    materials: Vec<Handle<ColorMaterial>>,
+    timer: Timer,
+    frame: usize,
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+//////////////////////
+// Components start
+//////////////////////
+
+#[derive(Debug)]
+pub struct Hazard {
+    pub kind: HazardKind,
+}
+
+// tile of a waterfall, rows further down are a frame ahead so the water runs
+#[derive(Debug)]
+struct WaterfallTile {
+    row: usize,
+}
+
+// the hazard volumes a body is in, and what they do to its movement
+#[derive(Debug)]
+pub struct Terrain {
+    water: u32,
+    spikes: u32,
+    waterfall: u32,
+    // seconds until the spikes hurt again
+    spikes_cooldown: f32,
+    // multiplies the speeds
+    pub speed_scale: f32,
+    // multiplies gravity
+    pub gravity_scale: f32,
+    pub max_fall_speed: Option<f32>,
+    // downwards acceleration on top of gravity
+    pub push: f32,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain {
+            water: 0,
+            spikes: 0,
+            waterfall: 0,
+            spikes_cooldown: 0.0,
+            speed_scale: 1.0,
+            gravity_scale: 1.0,
+            max_fall_speed: None,
+            push: 0.0,
+        }
+    }
+}
+
+impl Terrain {
+    pub fn in_water(&self) -> bool {
+        self.water > 0
+    }
+
+    fn count(&mut self, kind: HazardKind, delta: i32) {
+        let counter = match kind {
+            HazardKind::Water => &mut self.water,
+            HazardKind::Spikes => &mut self.spikes,
+            HazardKind::Waterfall => &mut self.waterfall,
+        };
+        *counter = (*counter as i32 + delta).max(0) as u32;
+    }
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(HazardTuning::load())
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_hazards.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(hazard_contacts.system().label("hazard_contacts"))
+                    .with_system(terrain_handler.system().after("hazard_contacts").before("controller"))
+                    .with_system(waterfall_animation.system()),
+            );
+    }
+}
+
+fn init_hazards(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    tuning: Res<HazardTuning>,
+    level: Res<LevelData>,
+) {
+    let water_material = materials.add(WATER_COLOR.into());
+    let spikes_material = materials.add(SPIKES_COLOR.into());
+    let waterfall_materials: Vec<Handle<ColorMaterial>> = (1..=WATERFALL_FRAMES)
+        .map(|frame| {
+            let texture = asset_server.load(format!("textures/rpg/tiles/generic-rpg-tile-waterfall{:02}.png", frame).as_str());
+            materials.add(texture.into())
+        })
+        .collect();
+
+    for desc in level.hazards.iter() {
+        let size = Vec2::new(desc.size.0, desc.size.1) * TILE_UNIT_TRANSLATION;
+        let translation = level::to_world(desc.pos, 1.0);
+
+        match desc.kind {
+            // water in front of the player, who is in it. spikes behind.
+            HazardKind::Water | HazardKind::Spikes => {
+                let (material, z) = if desc.kind == HazardKind::Water {
+                    (water_material.clone(), 1.1)
+                } else {
+                    (spikes_material.clone(), 0.9)
+                };
+                commands.spawn_bundle(SpriteBundle {
+                    sprite: Sprite::new(size),
+                    material,
+                    transform: Transform::from_translation(translation.truncate().extend(z)),
+                    visible: Visible {
+                        is_visible: true,
+                        is_transparent: true,
+                    },
+                    ..Default::default()
+                });
+            }
+            // tiles, behind the player
+            HazardKind::Waterfall => {
+                let columns = (size.x / TILE_GOALSIZE).round().max(1.0) as usize;
+                let rows = (size.y / TILE_GOALSIZE).round().max(1.0) as usize;
+                let top_left = translation.truncate() + Vec2::new(-size.x, size.y) / 2.0;
+                let scale = TILE_GOALSIZE / TILE_ACTUALSIZE;
+                for row in 0..rows {
+                    for column in 0..columns {
+                        let offset = Vec2::new(column as f32 + 0.5, -(row as f32 + 0.5)) * TILE_GOALSIZE;
+                        commands
+                            .spawn_bundle(SpriteBundle {
+                                material: waterfall_materials[row % WATERFALL_FRAMES].clone(),
+                                transform: Transform {
+                                    translation: (top_left + offset).extend(0.5),
+                                    scale: Vec3::new(scale, scale, 1.0),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            })
+                            .insert(WaterfallTile { row });
+                    }
+                }
+            }
+        }
+
+        commands
+            .spawn()
+            .insert(Transform::from_translation(translation))
+            .insert(GlobalTransform::default())
+            .insert(Hazard { kind: desc.kind })
+            .insert(RigidBody::Sensor)
+            .insert(Layer::Sensors.collision_layers())
+            .insert(CollisionShape::Cuboid {
+                half_extends: (size / 2.0).extend(1.0),
+                border_radius: None,
+            });
+    }
+
+    commands.insert_resource(WaterfallFrames {
+        materials: waterfall_materials,
+        timer: Timer::from_seconds(tuning.waterfall.frame_time, true),
+        frame: 0,
+    });
+}
+
+// counts the hazard volumes the player is in
+fn hazard_contacts(
+    mut events: EventReader<CollisionEvent>,
+    static_entities: Res<StaticEntities>,
+    hazards: Query<&Hazard>,
+    mut players: Query<&mut Terrain>,
+) {
+    let player = match static_entities.handles.get(&StaticEntityId::Player) {
+        Some(player) => *player,
+        None => return,
+    };
+    for event in events.iter() {
+        let (d1, d2, delta) = match event {
+            CollisionEvent::Started(d1, d2) => (d1, d2, 1),
+            CollisionEvent::Stopped(d1, d2) => (d1, d2, -1),
+        };
+        if let Some(entity) = collided_with_player(d1, d2, &static_entities) {
+            if let (Ok(hazard), Ok(mut terrain)) = (hazards.get(entity), players.get_mut(player)) {
+                terrain.count(hazard.kind, delta);
+            }
+        }
+    }
+}
+
+// works out the movement effects, and hurts the player on spikes
+fn terrain_handler(
+    time: Res<Time>,
+    tuning: Res<HazardTuning>,
+    mut bodies: Query<(Entity, &mut Terrain)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut terrain) in bodies.iter_mut() {
+        let water = terrain.in_water();
+        terrain.speed_scale = if water { tuning.water.speed_scale } else { 1.0 };
+        terrain.gravity_scale = if water { tuning.water.gravity_scale } else { 1.0 };
+        terrain.max_fall_speed = if water { Some(tuning.water.max_fall_speed) } else { None };
+        terrain.push = if terrain.waterfall > 0 { tuning.waterfall.push } else { 0.0 };
+
+        if terrain.spikes == 0 {
+            terrain.spikes_cooldown = 0.0;
+            continue;
+        }
+        terrain.spikes_cooldown -= time.delta_seconds();
+        if terrain.spikes_cooldown <= 0.0 {
+            terrain.spikes_cooldown = tuning.spikes.interval;
+            damage_events.send(DamageEvent {
+                target: entity,
+                amount: tuning.spikes.damage,
+                attacker: AttackerKind::Spikes,
+                allegiance: Allegiance::None,
+                source: None,
+            });
+        }
+    }
+}
+
+fn waterfall_animation(
+    time: Res<Time>,
+    mut frames: ResMut<WaterfallFrames>,
+    mut tiles: Query<(&WaterfallTile, &mut Handle<ColorMaterial>)>,
+) {
+    if !frames.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    // the water runs down, so each row shows the frame the row above had before
+    frames.frame = (frames.frame + WATERFALL_FRAMES - 1) % WATERFALL_FRAMES;
+    for (tile, mut material) in tiles.iter_mut() {
+        *material = frames.materials[(tile.row + frames.frame) % WATERFALL_FRAMES].clone();
+    }
+}
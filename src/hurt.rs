@@ -0,0 +1,139 @@
+// feedback and protection for whoever takes a hit: a knockback away from the attacker
+// and a short time of invincibility, during which the sprite blinks. both depend on the
+// attacker, see assets/data/hits.ron. the damage itself is dealt in creature.rs.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use serde::Deserialize;
+
+use crate::controller::Controller;
+use crate::creature::CreatureKind;
+use crate::AppState;
+
+const HITS_SOURCE: &str = include_str!("../assets/data/hits.ron");
+
+// seconds the sprite is shown or hidden while blinking
+const BLINK_INTERVAL: f32 = 0.08;
+
+// who dealt a hit
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AttackerKind {
+    Player,
+    Kunai,
+    Creature(CreatureKind),
+    Spikes,
+}
+
+#[derive(Deserialize, Default, Debug, Copy, Clone)]
+pub struct HitDesc {
+    knockback: (f32, f32),
+    stun: f32,
+    pub invincibility: f32,
+}
+
+// raised for every hit that got through, after the damage was dealt
+#[derive(Debug, Copy, Clone)]
+pub struct HurtEvent {
+    pub target: Entity,
+    pub attacker: AttackerKind,
+    // where the hit came from, none for hits without a side
+    pub source: Option<Vec2>,
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Debug)]
+pub struct HitTuning(HashMap<AttackerKind, HitDesc>);
+
+impl HitTuning {
+    fn load() -> Self {
+        HitTuning(ron::de::from_str(HITS_SOURCE).expect("assets/data/hits.ron is malformed"))
+    }
+
+    pub fn get(&self, attacker: AttackerKind) -> HitDesc {
+        self.0.get(&attacker).copied().unwrap_or_default()
+    }
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+//////////////////////
+// Components start
+//////////////////////
+
+// damage is ignored while this runs
+#[derive(Default, Debug)]
+pub struct Invincibility {
+    // seconds left
+    remaining: f32,
+}
+
+impl Invincibility {
+    pub fn active(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    pub fn start(&mut self, seconds: f32) {
+        self.remaining = self.remaining.max(seconds);
+    }
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct HurtPlugin;
+
+impl Plugin for HurtPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(HitTuning::load())
+            .add_event::<HurtEvent>()
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(knockback.system())
+                    .with_system(invincibility_blink.system()),
+            );
+    }
+}
+
+// throws the target away from the attacker. bodies with a controller lose sideways control
+// for a moment, so the throw isn't undone at once.
+fn knockback(
+    tuning: Res<HitTuning>,
+    mut events: EventReader<HurtEvent>,
+    mut bodies: Query<(&Transform, &mut Velocity, Option<&mut Controller>)>,
+) {
+    for event in events.iter() {
+        let desc = tuning.get(event.attacker);
+        if let Ok((transform, mut velocity, controller)) = bodies.get_mut(event.target) {
+            let away = match event.source {
+                Some(source) if transform.translation.x < source.x => -1.0,
+                Some(_) => 1.0,
+                None => 0.0,
+            };
+            velocity.linear = Vec3::new(away * desc.knockback.0, desc.knockback.1, 0.0);
+            if let Some(mut controller) = controller {
+                controller.lock_control(desc.stun);
+            }
+        }
+    }
+}
+
+fn invincibility_blink(time: Res<Time>, mut query: Query<(&mut Invincibility, &mut Visible)>) {
+    for (mut invincibility, mut visible) in query.iter_mut() {
+        if !invincibility.active() {
+            continue;
+        }
+        invincibility.remaining -= time.delta_seconds();
+        let shown = !invincibility.active() || invincibility.remaining % (2.0 * BLINK_INTERVAL) < BLINK_INTERVAL;
+        if visible.is_visible != shown {
+            visible.is_visible = shown;
+        }
+    }
+}
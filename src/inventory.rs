@@ -0,0 +1,496 @@
+// pickups lying around in the level, the player's inventory and the inventory panel.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use heron::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::level::{self, LevelData};
+use crate::{collided_with_player, AppState, Layer, StaticEntities, TILE_GOALSIZE};
+
+const INVENTORY_SLOTS: usize = 12;
+const INVENTORY_COLUMNS: usize = 4;
+const SLOT_SIZE: f32 = 42.0; // 3x the 14px inventario slot frame
+
+const SLOT_EMPTY: &str = "textures/rpg/ui/generic-rpg-ui-inventario01.png";
+const SLOT_FILLED: &str = "textures/rpg/ui/generic-rpg-ui-inventario02.png";
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    Sword,
+    Shield,
+    HealthPotion,
+    ManaPotion,
+    Apple,
+    Fish,
+    Flower,
+    // currency, spent and earned at the vendor
+    Coin,
+}
+
+impl ItemKind {
+    pub fn texture_path(self) -> &'static str {
+        match self {
+            ItemKind::Sword => "textures/rpg/props/generic-rpg-loot01.png",
+            ItemKind::Shield => "textures/rpg/props/generic-rpg-loot02.png",
+            ItemKind::HealthPotion => "textures/rpg/props/generic-rpg-loot03.png",
+            ItemKind::ManaPotion => "textures/rpg/props/generic-rpg-loot04.png",
+            ItemKind::Apple => "textures/rpg/props/generic-rpg-loot05.png",
+            ItemKind::Fish => "textures/rpg/props/generic-rpg-fish01.png",
+            ItemKind::Flower => "textures/rpg/props/generic-rpg-flower01.png",
+            ItemKind::Coin => "textures/rpg/ui/generic-rpg-ui-inventario04.png",
+        }
+    }
+
+    // how many of one kind fit into a single inventory slot.
+    pub fn max_stack(self) -> u32 {
+        match self {
+            ItemKind::Sword | ItemKind::Shield => 1,
+            ItemKind::HealthPotion | ItemKind::ManaPotion => 9,
+            ItemKind::Apple | ItemKind::Fish | ItemKind::Flower => 20,
+            ItemKind::Coin => 999,
+        }
+    }
+
+    // points added to the score per collected item.
+    pub fn score_value(self) -> u32 {
+        match self {
+            ItemKind::Sword | ItemKind::Shield => 50,
+            ItemKind::HealthPotion | ItemKind::ManaPotion => 20,
+            ItemKind::Apple | ItemKind::Fish | ItemKind::Flower => 5,
+            ItemKind::Coin => 1,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKind::Sword => "Sword",
+            ItemKind::Shield => "Shield",
+            ItemKind::HealthPotion => "Health Potion",
+            ItemKind::ManaPotion => "Mana Potion",
+            ItemKind::Apple => "Apple",
+            ItemKind::Fish => "Fish",
+            ItemKind::Flower => "Flower",
+            ItemKind::Coin => "Coin",
+        }
+    }
+
+    pub fn all() -> [ItemKind; 8] {
+        [
+            ItemKind::Sword,
+            ItemKind::Shield,
+            ItemKind::HealthPotion,
+            ItemKind::ManaPotion,
+            ItemKind::Apple,
+            ItemKind::Fish,
+            ItemKind::Flower,
+            ItemKind::Coin,
+        ]
+    }
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ItemStack {
+    pub kind: ItemKind,
+    pub count: u32,
+}
+
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory {
+            slots: vec![None; INVENTORY_SLOTS],
+        }
+    }
+}
+
+impl Inventory {
+    // fills up existing stacks of the same kind first, then empty slots.
+    // returns how many items did not fit.
+    pub fn add(&mut self, kind: ItemKind, count: u32) -> u32 {
+        let mut left = count;
+
+        for stack in self.slots.iter_mut().flatten() {
+            if left == 0 {
+                break;
+            }
+            if stack.kind == kind && stack.count < kind.max_stack() {
+                let moved = left.min(kind.max_stack() - stack.count);
+                stack.count += moved;
+                left -= moved;
+            }
+        }
+
+        for slot in self.slots.iter_mut() {
+            if left == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let moved = left.min(kind.max_stack());
+                *slot = Some(ItemStack { kind, count: moved });
+                left -= moved;
+            }
+        }
+
+        left
+    }
+
+    // removes `count` items of a kind, or nothing if there aren't enough.
+    pub fn remove(&mut self, kind: ItemKind, count: u32) -> bool {
+        if self.count(kind) < count {
+            return false;
+        }
+        let mut left = count;
+        // take from the back, so the first stacks stay full
+        for slot in self.slots.iter_mut().rev() {
+            if left == 0 {
+                break;
+            }
+            if let Some(stack) = slot {
+                if stack.kind == kind {
+                    let moved = left.min(stack.count);
+                    stack.count -= moved;
+                    left -= moved;
+                    if stack.count == 0 {
+                        *slot = None;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // whether `count` items of a kind would fit, without adding them.
+    pub fn can_add(&self, kind: ItemKind, count: u32) -> bool {
+        let space: u32 = self
+            .slots
+            .iter()
+            .map(|slot| match slot {
+                Some(stack) if stack.kind == kind => kind.max_stack() - stack.count,
+                Some(_) => 0,
+                None => kind.max_stack(),
+            })
+            .sum();
+        space >= count
+    }
+
+    pub fn count(&self, kind: ItemKind) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.kind == kind)
+            .map(|stack| stack.count)
+            .sum()
+    }
+}
+
+struct InventoryMaterials {
+    slot_empty: Handle<ColorMaterial>,
+    slot_filled: Handle<ColorMaterial>,
+    no_icon: Handle<ColorMaterial>,
+    icons: HashMap<ItemKind, Handle<ColorMaterial>>,
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+// raised whenever the player picks something up (score, achievements, quests...)
+#[derive(Debug, Copy, Clone)]
+pub struct PickupEvent {
+    pub kind: ItemKind,
+    pub count: u32,
+}
+
+//////////////////////
+// Components start
+//////////////////////
+
+#[derive(Debug)]
+pub struct Pickup {
+    pub kind: ItemKind,
+    pub count: u32,
+}
+
+// every node of the inventory panel (toggled together)
+#[derive(Debug)]
+struct InventoryUi;
+#[derive(Debug)]
+struct InventorySlotUi(usize);
+#[derive(Debug)]
+struct InventoryIconUi(usize);
+#[derive(Debug)]
+struct InventoryCountUi(usize);
+
+//////////////////////
+// Components end //
+//////////////////////
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Inventory>()
+            .add_event::<PickupEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_pickups.system())
+                    .with_system(init_inventory_ui.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(pickup_handler.system())
+                    .with_system(inventory_toggle.system())
+                    .with_system(inventory_ui_update.system()),
+            );
+    }
+}
+
+// spawn an item lying in the world. also used for loot dropped by chests etc.
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    materials: &mut Assets<ColorMaterial>,
+    kind: ItemKind,
+    count: u32,
+    translation: Vec3,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::splat(TILE_GOALSIZE)),
+            material: materials.add(asset_server.load(kind.texture_path()).into()),
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        })
+        .insert(Pickup { kind, count })
+        .insert(RigidBody::Sensor)
+        .insert(Layer::Pickups.collision_layers())
+        .insert(CollisionShape::Cuboid {
+            half_extends: Vec3::new(TILE_GOALSIZE / 2.0, TILE_GOALSIZE / 2.0, 1.0),
+            border_radius: None,
+        })
+        .id()
+}
+
+fn init_pickups(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    level: Res<LevelData>,
+) {
+    for desc in level.pickups.iter() {
+        spawn_pickup(
+            &mut commands,
+            &asset_server,
+            &mut materials,
+            desc.item,
+            desc.count,
+            level::to_world(desc.pos, 1.0),
+        );
+    }
+}
+
+fn init_inventory_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mut icons = HashMap::new();
+    for kind in ItemKind::all().iter() {
+        icons.insert(*kind, materials.add(asset_server.load(kind.texture_path()).into()));
+    }
+    let inventory_materials = InventoryMaterials {
+        slot_empty: materials.add(asset_server.load(SLOT_EMPTY).into()),
+        slot_filled: materials.add(asset_server.load(SLOT_FILLED).into()),
+        no_icon: materials.add(Color::NONE.into()),
+        icons,
+    };
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let columns = INVENTORY_COLUMNS;
+    let rows = INVENTORY_SLOTS / INVENTORY_COLUMNS;
+
+    // panel in the bottom right corner, hidden until toggled
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(
+                    Val::Px(SLOT_SIZE * columns as f32 + 10.0),
+                    Val::Px(SLOT_SIZE * rows as f32 + 10.0),
+                ),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                padding: Rect::all(Val::Px(5.0)),
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                ..Default::default()
+            },
+            material: materials.add(Color::rgba(0.2, 0.15, 0.1, 0.85).into()),
+            visible: Visible {
+                is_visible: false,
+                is_transparent: true,
+            },
+            ..Default::default()
+        })
+        .insert(InventoryUi)
+        .with_children(|parent| {
+            for index in 0..INVENTORY_SLOTS {
+                parent
+                    .spawn_bundle(ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(SLOT_SIZE), Val::Px(SLOT_SIZE)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        material: inventory_materials.slot_empty.clone(),
+                        visible: Visible {
+                            is_visible: false,
+                            is_transparent: true,
+                        },
+                        ..Default::default()
+                    })
+                    .insert(InventoryUi)
+                    .insert(InventorySlotUi(index))
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(ImageBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(SLOT_SIZE - 14.0), Val::Px(SLOT_SIZE - 14.0)),
+                                    ..Default::default()
+                                },
+                                material: inventory_materials.no_icon.clone(),
+                                visible: Visible {
+                                    is_visible: false,
+                                    is_transparent: true,
+                                },
+                                ..Default::default()
+                            })
+                            .insert(InventoryUi)
+                            .insert(InventoryIconUi(index));
+                        // stack size, bottom right of the slot
+                        parent
+                            .spawn_bundle(TextBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    position: Rect {
+                                        right: Val::Px(3.0),
+                                        bottom: Val::Px(1.0),
+                                        ..Default::default()
+                                    },
+                                    ..Default::default()
+                                },
+                                text: Text::with_section(
+                                    "",
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 14.0,
+                                        color: Color::WHITE,
+                                    },
+                                    Default::default(),
+                                ),
+                                visible: Visible {
+                                    is_visible: false,
+                                    is_transparent: true,
+                                },
+                                ..Default::default()
+                            })
+                            .insert(InventoryUi)
+                            .insert(InventoryCountUi(index));
+                    });
+            }
+        });
+
+    commands.insert_resource(inventory_materials);
+}
+
+fn pickup_handler(
+    mut commands: Commands,
+    mut events: EventReader<CollisionEvent>,
+    mut pickup_events: EventWriter<PickupEvent>,
+    mut inventory: ResMut<Inventory>,
+    static_entities: Res<StaticEntities>,
+    query: Query<&Pickup>,
+) {
+    for event in events.iter() {
+        if let CollisionEvent::Started(d1, d2) = event {
+            let entity = match collided_with_player(d1, d2, &static_entities) {
+                Some(entity) => entity,
+                None => continue,
+            };
+            if let Ok(pickup) = query.get(entity) {
+                let left = inventory.add(pickup.kind, pickup.count);
+                let taken = pickup.count - left;
+                if taken == 0 {
+                    // inventory full, leave it lying around
+                    continue;
+                }
+                if left == 0 {
+                    commands.entity(entity).despawn();
+                } else {
+                    commands.entity(entity).insert(Pickup {
+                        kind: pickup.kind,
+                        count: left,
+                    });
+                }
+                pickup_events.send(PickupEvent {
+                    kind: pickup.kind,
+                    count: taken,
+                });
+            }
+        }
+    }
+}
+
+fn inventory_toggle(
+    keys: Res<Input<KeyCode>>,
+    mut query: Query<&mut Visible, With<InventoryUi>>,
+) {
+    if keys.just_pressed(KeyCode::I) {
+        for mut visible in query.iter_mut() {
+            visible.is_visible = !visible.is_visible;
+        }
+    }
+}
+
+fn inventory_ui_update(
+    inventory: Res<Inventory>,
+    inventory_materials: Res<InventoryMaterials>,
+    mut slots: Query<(&InventorySlotUi, &mut Handle<ColorMaterial>), Without<InventoryIconUi>>,
+    mut icons: Query<(&InventoryIconUi, &mut Handle<ColorMaterial>), Without<InventorySlotUi>>,
+    mut counts: Query<(&InventoryCountUi, &mut Text)>,
+) {
+    if !inventory.is_changed() {
+        return;
+    }
+
+    for (slot, mut material) in slots.iter_mut() {
+        *material = match inventory.slots[slot.0] {
+            Some(_) => inventory_materials.slot_filled.clone(),
+            None => inventory_materials.slot_empty.clone(),
+        };
+    }
+    for (icon, mut material) in icons.iter_mut() {
+        *material = match inventory.slots[icon.0] {
+            Some(stack) => inventory_materials.icons[&stack.kind].clone(),
+            None => inventory_materials.no_icon.clone(),
+        };
+    }
+    for (count, mut text) in counts.iter_mut() {
+        text.sections[0].value = match inventory.slots[count.0] {
+            Some(stack) if stack.count > 1 => stack.count.to_string(),
+            _ => String::new(),
+        };
+    }
+}
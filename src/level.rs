@@ -0,0 +1,158 @@
+// level layout, read from assets/data/level.ron
+//
+// positions in the level file are given in multiples of TILE_UNIT_TRANSLATION
+// (half a tile), same as the hardcoded translations in init_objects.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{Allegiance, TILE_UNIT_TRANSLATION};
+use crate::creature::CreatureKind;
+use crate::gate::GateIdentifier;
+use crate::hazard::HazardKind;
+use crate::inventory::ItemKind;
+use crate::npc::NpcKind;
+
+// compiled in, so native and wasm builds read the same file without a filesystem.
+const LEVEL_SOURCE: &str = include_str!("../assets/data/level.ron");
+
+#[derive(Deserialize, Default)]
+pub struct LevelData {
+    #[serde(default)]
+    pub pickups: Vec<PickupDesc>,
+    #[serde(default)]
+    pub chests: Vec<ChestDesc>,
+    #[serde(default)]
+    pub npcs: Vec<NpcDesc>,
+    #[serde(default)]
+    pub creatures: Vec<CreatureDesc>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerDesc>,
+    #[serde(default)]
+    pub gates: Vec<GateDesc>,
+    #[serde(default)]
+    pub spawns: Vec<SpawnDesc>,
+    #[serde(default)]
+    pub solids: Vec<SolidDesc>,
+    #[serde(default)]
+    pub platforms: Vec<PlatformDesc>,
+    #[serde(default)]
+    pub hazards: Vec<HazardDesc>,
+    // the camera doesn't show anything outside, none for a camera without limits
+    #[serde(default)]
+    pub bounds: Option<BoundsDesc>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PickupDesc {
+    pub item: ItemKind,
+    #[serde(default = "one")]
+    pub count: u32,
+    pub pos: (f32, f32),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ChestDesc {
+    // unique, used to remember opened chests in the save data
+    pub id: String,
+    // key into assets/data/loot.ron
+    pub loot: String,
+    pub pos: (f32, f32),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct NpcDesc {
+    pub kind: NpcKind,
+    // key into the dialogue trees (assets/data/dialogue/<name>.ron)
+    pub dialogue: String,
+    pub pos: (f32, f32),
+    // talk radius in pixels, defaults per npc module
+    #[serde(default)]
+    pub radius: Option<f32>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct CreatureDesc {
+    pub kind: CreatureKind,
+    pub pos: (f32, f32),
+    // faction, defaults per creature kind
+    #[serde(default)]
+    pub allegiance: Option<Allegiance>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct TriggerDesc {
+    // raised with the TriggerEvent when the player walks in
+    pub id: String,
+    pub pos: (f32, f32),
+    // width and height, in half tiles as well
+    pub size: (f32, f32),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct GateDesc {
+    pub gate: GateIdentifier,
+    pub pos: (f32, f32),
+    // width and height, in half tiles
+    pub size: (f32, f32),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SpawnDesc {
+    // "start" is the default spawn, others are picked with the spawn url parameter
+    pub id: String,
+    pub pos: (f32, f32),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SolidDesc {
+    pub pos: (f32, f32),
+    // width and height, in half tiles
+    pub size: (f32, f32),
+    // the player can climb up and down its sides
+    #[serde(default)]
+    pub climbable: bool,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PlatformDesc {
+    pub pos: (f32, f32),
+    // width and height, in half tiles
+    pub size: (f32, f32),
+    // positions it moves through after pos, then back to pos. empty for a platform that stays put.
+    #[serde(default)]
+    pub path: Vec<(f32, f32)>,
+    // half tiles per second
+    #[serde(default)]
+    pub speed: f32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct HazardDesc {
+    pub kind: HazardKind,
+    pub pos: (f32, f32),
+    // width and height, in half tiles
+    pub size: (f32, f32),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BoundsDesc {
+    // bottom left and top right corners
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl LevelData {
+    pub fn load() -> Self {
+        ron::de::from_str(LEVEL_SOURCE).expect("assets/data/level.ron is malformed")
+    }
+}
+
+// level position (in half tiles) to world translation.
+pub fn to_world(pos: (f32, f32), z: f32) -> Vec3 {
+    Vec3::new(pos.0 * TILE_UNIT_TRANSLATION, pos.1 * TILE_UNIT_TRANSLATION, z)
+}
@@ -28,6 +28,59 @@ use heron::RigidBody as RigidBodyEnum;
 
 use heron::rapier_plugin::rapier2d::dynamics::RigidBody as RigidBodyStruct;
 use heron::rapier_plugin::rapier2d::dynamics::RigidBodyBuilder;
+use heron::CollisionData;
+
+// game modules
+mod level;
+mod inventory;
+mod save;
+mod chest;
+mod npc;
+mod dialogue;
+mod shop;
+mod creature;
+mod trigger;
+mod quest;
+mod gate;
+mod spawn;
+mod ground;
+mod controller;
+mod platform;
+mod slide;
+mod air_attack;
+mod hazard;
+mod hurt;
+mod faction;
+mod camera;
+mod display;
+mod animation;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+use inventory::{InventoryPlugin, PickupEvent};
+use save::SavePlugin;
+use chest::ChestPlugin;
+use npc::NpcPlugin;
+use dialogue::{DialoguePlugin, DialogueState};
+use shop::{ShopPlugin, ShopState};
+use creature::{CreatureInfo, CreaturePlugin, DamageEvent};
+use trigger::TriggerPlugin;
+use quest::QuestPlugin;
+use gate::GatePlugin;
+use spawn::{SpawnPlugin, SpawnRequest};
+use ground::{Contacts, GroundPlugin, LandedEvent};
+use controller::{ActionDesc, Controller, ControllerPlugin, JumpedEvent, MoveState};
+use platform::{PlatformPlugin, Rider};
+use slide::{SlidePlugin, SlideShape};
+use air_attack::{AirAttack, AirAttackPlugin};
+use hazard::{HazardPlugin, Terrain};
+use hurt::{AttackerKind, HurtPlugin, Invincibility};
+use faction::FactionPlugin;
+use camera::{CameraFollow, CameraPlugin};
+use display::DisplayPlugin;
+use animation::{AnimationClips, AnimationPlugin, Animator};
+#[cfg(target_arch = "wasm32")]
+use web::WebPlugin;
 ////////////////////////////////
 // Global consts/vars start //
 ////////////////////////////////
@@ -56,6 +109,9 @@ const WIN_HEIGHT : f32 = (TILE_GOALSIZE * (TILES_Y as f32) );
 const PLAYER_SCALE_X : f32 = 0.15 as f32;
 const PLAYER_SCALE_Y : f32 = 0.15 as f32;
 
+// the player's faction, creatures sharing it are allies (faction.rs)
+const PLAYER_ALLEGIANCE: Allegiance = Allegiance::Blue;
+
 const MOVE_STEPS : u32 = 4;
 
 // how far do sprites move each step.
@@ -84,24 +140,11 @@ const TIME_STEP: f32 = 1. / 60.;
 // global vars accessed externally
 ///////////////////////////////////
 
-// variable accessed by javascript keydown eventhandler continuously
-// to check if and what game object has been 'accessed' by player.
-
-#[cfg(target_arch = "wasm32")]
-static mut PAGE_ID : GateIdentifier = GateIdentifier::None;
-
 static WAIT_TIME: u64 = 2;
 
+// opens chests, starts conversations, etc.
+const KEY_INTERACT: KeyCode = KeyCode::E;
 
-#[derive(Copy, Clone)]
-enum ExtGameObjectId {
-    None = 3000,
-    Home = 3001,
-    Blog = 3002,
-    Markets = 3003,
-    Portfolio = 3004,
-    Contact = 3005 // rename to 'About'
-}
 
 enum MovementDir {
     MoveLeft,
@@ -117,29 +160,42 @@ enum MovementDir {
     MovementDownRight
 }
 
-#[cfg(target_arch = "wasm32")]
-#[derive(PartialEq, Eq, Hash, Copy, Clone)]
-enum GateIdentifier {
-    None = 3000,
-    Home = 3001,
-    Blog = 3002,
-    Markets = 3003,
-    About = 3004
-}
-
 #[derive(PartialEq, Eq, Hash, Copy, Clone)]
 enum AnimState {
     Idle = 4000,
     Run = 4001,
     Attack = 4002,
     Jump = 4003,
-    Glide = 4004
+    Glide = 4004,
+    Climb = 4005,
+    Slide = 4006,
+    JumpAttack = 4007,
+    JumpThrow = 4008
+}
+
+impl AnimState {
+    // name of the animation clip in assets/data/animations.ron
+    fn clip(self) -> &'static str {
+        match self {
+            AnimState::Idle => "player/idle",
+            AnimState::Run => "player/run",
+            AnimState::Attack => "player/attack",
+            AnimState::Jump => "player/jump",
+            AnimState::Glide => "player/glide",
+            AnimState::Climb => "player/climb",
+            AnimState::Slide => "player/slide",
+            AnimState::JumpAttack => "player/jump_attack",
+            AnimState::JumpThrow => "player/jump_throw",
+        }
+    }
 }
 
 #[derive(Default)]
 struct AnimStateTuple {
     old: Option<AnimState>,
-    current: Option<AnimState>
+    current: Option<AnimState>,
+    // current was entered again (a new attack right as the last one ended), its clip starts over
+    replay: bool
 }
 
 
@@ -176,46 +232,31 @@ enum StaticEntityId {
 
 #[derive(Default)]
 struct Timers {
-    gate_timer: Timer,
     movement_timer: Timer,
 
-    anim_run_timer: Timer, // start once player presses jump. only attacks can interrupt the jump. timer property of playerbundle sets the animation speed.
+    anim_run_timer: Timer, // start once player presses jump. only attacks can interrupt the jump. animation speeds are in assets/data/animations.ron.
 //    anim_idle_timer: Timer, // 
-    anim_attack_timer: Timer, // start once player presses attack. only jumps can interrupt the attack. timer property of playerbundle sets the animation speed.
-    anim_jump_timer: Timer
+    anim_attack_timer: Timer, // start once player presses attack. only jumps can interrupt the attack. animation speeds are in assets/data/animations.ron.
 }
 
 #[derive(Default)]
-struct StaticEntities {
-    handles: HashMap<StaticEntityId, Entity>,
-}
+struct Score(u32);
 
-#[derive(Default)]
-struct AtlasHandles {
-    player: HashMap<AnimState, Handle<TextureAtlas>>,
-    // ...
-    idle: Handle<TextureAtlas>,
-    run: Handle<TextureAtlas>,
-    attack: Handle<TextureAtlas>,
-    background: Handle<TextureAtlas>
+// move the player somewhere else instantly (level position, in half tiles)
+#[derive(Debug, Copy, Clone)]
+pub struct TeleportEvent {
+    pub pos: (f32, f32),
 }
 
-// holding tl and br points of all instantiated objects(excluding player)
-#[cfg(target_arch = "wasm32")]
 #[derive(Default)]
-struct Points {                    //topleft, bottomright
-    hash_map: HashMap<GateIdentifier, (Vec3, Vec3)>,
+struct StaticEntities {
+    handles: HashMap<StaticEntityId, Entity>,
 }
 
+// atlases of the animation clips with a source, by clip name (animation.rs)
 #[derive(Default)]
-struct ActionDesc {
-    vel_step: Vec3, // step in which speed increases
-    vel_max: Vec3, // max speed.
-
-    acc_step: Vec3, // step in which acceleration increases.
-    acc_max: Vec3, // max acceleration
-
-    move_dir: Option<MovementDir>
+struct AtlasHandles {
+    clips: HashMap<String, Handle<TextureAtlas>>,
 }
 
 struct BoxTexture {
@@ -225,7 +266,8 @@ struct BoxTexture {
 #[derive(Default)]
 struct SpriteHandles {
     // unit tiles
-    player_new: HashMap<AnimState, Vec<HandleUntyped>>,
+    // frames of the animation clips with a source, by clip name (animation.rs)
+    clips: HashMap<String, Vec<HandleUntyped>>,
 //    player: Vec<HandleUntyped>, //consider making a hashmap, key: enum (Idle, Running, etc.) value: Vec<HandleUnTyped>
     opponent: Vec<HandleUntyped>,
     // ground tiles (16px width, 16px height)
@@ -285,15 +327,71 @@ enum AppStages {
     Run
 }
 
-/*
-// Define your physics layers (Heron)
-#[derive(PhysicsLayer)]
+// physics layers (Heron). every collider is in exactly one layer and only
+// collides with the layers listed in Layer::collision_layers.
+#[derive(PhysicsLayer, Debug, Copy, Clone, PartialEq, Eq)]
 enum Layer {
     World,
     Player,
     Enemies,
+    // creatures on the player's side. the player walks through them, projectiles still
+    // reach them for friendly fire.
+    Allies,
+    Projectiles,
+    // gates, triggers
+    Sensors,
+    Pickups,
+    // the player's ground and wall sensors (ground.rs)
+    ContactSensors,
+    // one-way and moving platforms. the player lands on them without physics (platform.rs)
+    Platforms,
+}
+
+impl Layer {
+    fn collision_layers(self) -> CollisionLayers {
+        let masks: &[Layer] = match self {
+            Layer::World => &[Layer::Player, Layer::Enemies, Layer::Allies, Layer::Projectiles, Layer::ContactSensors],
+            Layer::Player => &[Layer::World, Layer::Enemies, Layer::Sensors, Layer::Pickups],
+            Layer::Enemies => &[Layer::World, Layer::Player, Layer::Projectiles],
+            Layer::Allies => &[Layer::World, Layer::Projectiles],
+            Layer::Projectiles => &[Layer::World, Layer::Enemies, Layer::Allies, Layer::Platforms],
+            Layer::Sensors | Layer::Pickups => &[Layer::Player],
+            Layer::ContactSensors => &[Layer::World],
+            Layer::Platforms => &[Layer::Projectiles],
+        };
+        CollisionLayers::none()
+            .with_group(self)
+            .with_masks(masks.iter().copied())
+    }
+
+    // layer of a creature, by whose side it is on
+    fn of_creature(allegiance: Allegiance) -> Layer {
+        if allegiance.friendly_to(PLAYER_ALLEGIANCE) {
+            Layer::Allies
+        } else {
+            Layer::Enemies
+        }
+    }
+
+    // layer of one side of a collision
+    fn of(data: &CollisionData) -> Option<Layer> {
+        let layers = data.collision_layers();
+        [
+            Layer::World,
+            Layer::Player,
+            Layer::Enemies,
+            Layer::Allies,
+            Layer::Projectiles,
+            Layer::Sensors,
+            Layer::Pickups,
+            Layer::ContactSensors,
+            Layer::Platforms,
+        ]
+            .iter()
+            .copied()
+            .find(|layer| layers.contains_group(*layer))
+    }
 }
-*/
 
 ////////////////////
 // Resources end //
@@ -334,16 +432,26 @@ struct IsPlayer(bool);
 struct IsMovable(bool);
 #[derive(Debug)]
 struct Health(f32);
+// the player's health, also after respawning
+const PLAYER_MAX_HEALTH: f32 = 100.0;
 #[derive(Debug)]
 struct AttackPoints(f32);
 
-enum Allegiance {
+// faction of a body (faction.rs). None belongs to no faction and is everybody's foe.
+#[derive(serde::Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Allegiance {
     Blue=     1700,
     Red=      1701,
     Yellow=   1702,
     None=     1703
 }
 
+impl Allegiance {
+    fn friendly_to(self, other: Allegiance) -> bool {
+        self != Allegiance::None && self == other
+    }
+}
+
 // integrity of a structure.
 #[derive(Debug)]
 struct Integrity(f32);
@@ -358,6 +466,8 @@ struct InGameUi;
 #[derive(Debug)]
 struct MainMenuUi;
 #[derive(Debug)]
+struct ScoreText;
+#[derive(Debug)]
 struct Player;
 #[derive(Debug)]
 struct Creature;
@@ -392,6 +502,7 @@ struct PlayerBundle {
 
     health: Health,
     attack_points: AttackPoints,
+    allegiance: Allegiance,
     old_current: AnimStateTuple, // two elements vector.
 
     #[bundle]
@@ -404,6 +515,7 @@ struct CreatureBundle {
 
     health: Health,
     attack_points: AttackPoints,
+    allegiance: Allegiance,
 
     sprite_sheet: SpriteSheetBundle,
 }
@@ -414,6 +526,7 @@ struct BossBundle {
 
     health: Health,
     attack_points: AttackPoints,
+    allegiance: Allegiance,
 
     #[bundle]
     sprite: SpriteBundle,
@@ -426,6 +539,7 @@ struct StructureBundle {
 
     integrity: Integrity, // Integrity: 'health points' of structure
     is_movable: IsMovable,
+    allegiance: Allegiance,
 
     #[bundle]
     sprite: SpriteSheetBundle  
@@ -434,6 +548,7 @@ struct StructureBundle {
 #[derive(Bundle)]
 struct ProjectileBundle {
     query_marker: Projectile,
+    allegiance: Allegiance,
 
     #[bundle]
     sprite: SpriteBundle  
@@ -491,24 +606,26 @@ struct MainMenuUiBundle {
 pub fn run() {
     let mut app = App::build();
     app.init_resource::<Timers>()
-//        .init_resource::<Points>()
-        .init_resource::<ActionDesc>()
         .init_resource::<SpriteHandles>()
         .init_resource::<StaticEntities>()
         .init_resource::<AtlasHandles>()
+        .init_resource::<Score>()
+        .add_event::<TeleportEvent>()
         .init_resource::<Vec<MovementDir>>()
         .insert_resource(WindowDescriptor {
             title: "Zhneeshgame!".to_string(),
             width: WIN_WIDTH,
             height: WIN_HEIGHT,
-            resizable: false,
+            // the view is scaled to any size, see display.rs
+            resizable: true,
             ..Default::default()
         })
         .insert_resource(
             ClearColor( Color::rgba(0.4, 1.0, 0.8, 0.1)) 
         )
+        // no gravity in the physics world, the player controller applies its own (controller.rs)
         .insert_resource(
-                    Gravity::from(Vec3::new(0.0, -9.81, 0.0))
+                    Gravity::from(Vec3::ZERO)
         );
 
     // plugins start
@@ -520,6 +637,30 @@ pub fn run() {
     app.add_plugin( PhysicsPlugin::default() );
     app.add_plugin( LogDiagnosticsPlugin::default() );
     app.add_plugin( FrameTimeDiagnosticsPlugin::default() );
+    app.add_plugin( SavePlugin );
+    app.add_plugin( InventoryPlugin );
+    app.add_plugin( ChestPlugin );
+    app.add_plugin( NpcPlugin );
+    app.add_plugin( DialoguePlugin );
+    app.add_plugin( ShopPlugin );
+    app.add_plugin( CreaturePlugin );
+    app.add_plugin( TriggerPlugin );
+    app.add_plugin( QuestPlugin );
+    app.add_plugin( GatePlugin );
+    app.add_plugin( SpawnPlugin );
+    app.add_plugin( GroundPlugin );
+    app.add_plugin( ControllerPlugin );
+    app.add_plugin( PlatformPlugin );
+    app.add_plugin( SlidePlugin );
+    app.add_plugin( AirAttackPlugin );
+    app.add_plugin( HazardPlugin );
+    app.add_plugin( HurtPlugin );
+    app.add_plugin( FactionPlugin );
+    app.add_plugin( CameraPlugin );
+    app.add_plugin( DisplayPlugin );
+    app.add_plugin( AnimationPlugin );
+    #[cfg(target_arch = "wasm32")]
+    app.add_plugin( WebPlugin );
     // plugins end
     app.add_state(AppState::Load)
     // system only runs in load state
@@ -547,23 +688,44 @@ pub fn run() {
         .add_system_set(
             SystemSet::on_update(AppState::Ready)
                 .with_system(player_input.system().label("input") )
-                .with_system(player_animation.system().after("input") )
+                .with_system(player_animation.system().label("player_animation").after("input") )
                 .with_system(collision_handler.system())
-                .with_system(camera_handler.system())
+                .with_system(landing_handler.system())
+                .with_system(score_handler.system())
+                .with_system(teleport_handler.system())
         )
         .run();
 }
 
+// transform of the player entity, if it has been spawned already.
+fn player_transform<'a>(
+    static_entities: &StaticEntities,
+    players: &'a Query<&Transform, With<Player>>,
+) -> Option<&'a Transform> {
+    let player = static_entities.handles.get(&StaticEntityId::Player)?;
+    players.get(*player).ok()
+}
+
+// if one side of a collision is the player's rigid body, returns the rigid body entity on the other side.
+fn collided_with_player(
+    d1: &CollisionData,
+    d2: &CollisionData,
+    static_entities: &StaticEntities,
+) -> Option<Entity> {
+    let player = *static_entities.handles.get(&StaticEntityId::Player)?;
+    if d1.rigid_body_entity() == player {
+        Some(d2.rigid_body_entity())
+    } else if d2.rigid_body_entity() == player {
+        Some(d1.rigid_body_entity())
+    } else {
+        None
+    }
+}
+
 ////////////////////////////////////////
 // externally linked function (javascript)
 ////////////////////////////////////////
-#[cfg(target_arch = "wasm32")]
-pub fn link_established() -> u8 {
-    unsafe {
-        let ret: u8 = (PAGE_ID as u8);
-        return ret;
-    }
-}
+// the javascript api (start/stop, gate callbacks, teleport, ...) is in web.rs, wasm only.
 
 //////////////////
 // Systems start
@@ -573,8 +735,7 @@ fn load_resources(
         mut commands: Commands,
         asset_server: Res<AssetServer>,
         mut materials: ResMut<Assets<ColorMaterial>>,
-        mut windows: ResMut<Windows>,
-        mut actions: ResMut<ActionDesc>,
+        windows: Res<Windows>,
         mut handles: ResMut<SpriteHandles>,
         mut timers: ResMut<Timers>,
         mut texture_atlases: ResMut<Assets<TextureAtlas>>,
@@ -587,30 +748,19 @@ fn load_resources(
         player_materials: materials.add(texture_handle.into())
     });
     
-    let mut window = windows.get_primary_mut().unwrap();
-    window.set_position(IVec2::new(640, 480));
+    let window = windows.get_primary().unwrap();
 
-    // insert ressource object holding window size into container of Ressources
+    // insert ressource object holding window size into container of Ressources (kept up to date in display.rs)
     commands.insert_resource(WinSize {
         w: window.width(),
         h: window.height()
     });
 
-    // timer regulating the PAGE_ID global variable changes. 
-    timers.gate_timer = Timer::from_seconds(4.0, true);
     // timer regulating the keydown events that move the player.
     timers.movement_timer = Timer::from_seconds(0.0, true);
 
     // add timer only. block all movements and jumps until timer complete.
     timers.anim_attack_timer = Timer::from_seconds(0.5, false);
-    // timer on jump, but anim also dependent on a landing event. if no landing, switch to 'glide anim' after timer ends until landing
-    timers.anim_jump_timer = Timer::from_seconds(0.7, false);
-
-    actions.vel_step = Vec3::new(5.0, 5.0, 5.0);
-    actions.vel_max = Vec3::new(20.0, 20.0, 20.0);
-
-    actions.acc_step = Vec3::new(5.0, 5.0, 5.0);
-    actions.acc_max = Vec3::new(5.0, 5.0, 5.0);
 
     // load box texture as globally accessible ressource
     commands.insert_resource(BoxTexture {
@@ -625,6 +775,9 @@ fn load_resources(
     handles.home = asset_server.load("textures/rpg/props/generic-rpg-mini-lake.png").into();
 
     handles.background = Some( asset_server.load_untyped("textures/background02.jpg") );
+
+    // level layout (pickups, ...)
+    commands.insert_resource(level::LevelData::load());
     
     // ?POSSIBLE? : can afford to 'clone weak' because texture handle is stored on the asset server
     // TODO : fix this! error loading handles in init_world
@@ -643,12 +796,8 @@ fn init_camera(
         query_marker: Camera2d,
         ortho_bundle: OrthographicCameraBundle::new_2d()
         })
-        .insert(
-            RigidBody::Dynamic
-        )
-        .insert(
-            Velocity::from_linear(Vec3::X * 0.0)
-        )
+        // moved by camera.rs
+        .insert(CameraFollow::default())
         .id();
 
     println!("Initialized cameras!");
@@ -722,14 +871,14 @@ fn init_gui(
                             ..Default::default()
                         })
                         .with_children(|parent| {
-                            // text
+                            // score text
                             parent.spawn_bundle(TextBundle {
                                 style: Style {
                                     margin: Rect::all(Val::Px(5.0)),
                                     ..Default::default()
                                 },
                                 text: Text::with_section(
-                                    "Text Example",
+                                    "Score: 0",
                                     TextStyle {
                                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                         font_size: 20.0,
@@ -738,7 +887,8 @@ fn init_gui(
                                     Default::default(),
                                 ),
                                 ..Default::default()
-                            });
+                            })
+                            .insert(ScoreText);
                         });
                     // second column
                     parent
@@ -915,33 +1065,26 @@ fn init_gui(
 
 /*
     NEEDS to run AFTER player_input. like this we can save old anim_state, and check if changed in player_input here.
-    If changed -> set sprite.index to 0 (texture_atlas_handle changed in player_input), and update old animState, and set new handle to texture atlas.
-    Else -> just continue incrementing.
+    If changed -> start the clip of the new animState on the animator (animation.rs), and update old animState.
+    Else -> the animator keeps playing.
 */
 
 fn player_animation(
-    time: Res<Time>,
-    texture_atlases: Res<Assets<TextureAtlas>>,
-    mut atlas_handles: Res<AtlasHandles>,
-    mut query: Query<(&mut Timer, Option<&Player>, &mut AnimStateTuple, &mut TextureAtlasSprite, &mut Handle<TextureAtlas>)>,
-    mut timers: ResMut<Timers>
+    mut query: Query<(&mut AnimStateTuple, &mut Animator), With<Player>>,
 )   {
-        timers.gate_timer.tick( time.delta() );
-
-        // TODO: access player entity directly, no loop
-        for (mut timer, player, mut tuple, mut sprite, mut texture_atlas_handle) in query.iter_mut() {
-            if let Some(result) = player {
-                timer.tick( time.delta() );
-                if timer.finished() {
-                    if Some(tuple.old) != Some(tuple.current) {
-                        sprite.index = 0;
-                        *texture_atlas_handle = atlas_handles.player.get(&tuple.current.unwrap()).unwrap().clone_weak();
-                        tuple.old = tuple.current;
-                    }
-                    let texture_atlas = texture_atlases.get( (*texture_atlas_handle).clone_weak() ).unwrap();
-                    // increment the index of texture atlas with each tick
-                    sprite.index = ((sprite.index as usize + 1) % texture_atlas.textures.len()) as u32;
+        for (mut tuple, mut animator) in query.iter_mut() {
+            if tuple.old != tuple.current {
+                tuple.old = tuple.current;
+                if let Some(current) = tuple.current {
+                    animator.play(current.clip());
                 }
+            } else if tuple.replay {
+                if let Some(current) = tuple.current {
+                    animator.restart(current.clip());
+                }
+            }
+            if tuple.replay {
+                tuple.replay = false;
             }
         }
 }
@@ -949,15 +1092,15 @@ fn player_animation(
 fn load_textures(
     mut sprite_handles: ResMut<SpriteHandles>,
     mut textures: ResMut<Assets<Texture>>,
-    asset_server: Res<AssetServer>
+    asset_server: Res<AssetServer>,
+    clips: Res<AnimationClips>,
 )   {
-        sprite_handles.player_new.insert( AnimState::Idle, asset_server.load_folder("ninja/png/Idle").unwrap());
-        sprite_handles.player_new.insert( AnimState::Run, asset_server.load_folder("ninja/png/Run").unwrap());
-        sprite_handles.player_new.insert( AnimState::Jump, asset_server.load_folder("ninja/png/Jump").unwrap());
-        sprite_handles.player_new.insert( AnimState::Attack, asset_server.load_folder("ninja/png/Attack").unwrap());
-        sprite_handles.player_new.insert( AnimState::Glide, asset_server.load_folder("ninja/png/Glide").unwrap());
-
-        
+        // frames of the animation clips (assets/data/animations.ron)
+        for (name, clip) in clips.0.iter() {
+            if let Some(source) = &clip.source {
+                sprite_handles.clips.insert( name.clone(), source.load(&asset_server) );
+            }
+        }
 }
 
 
@@ -973,7 +1116,7 @@ fn check_textures(
         let mut finished_home : bool = false;
         let mut finished_background : bool = false;
 
-        for (anim_type, vec) in sprite_handles.player_new.iter() {
+        for (clip, vec) in sprite_handles.clips.iter() {
             if asset_server.get_group_load_state(
                 vec.iter().map(|handle| handle.id)
             ) != LoadState::Loaded {
@@ -1027,17 +1170,26 @@ fn build_texture_atlases(
 )   {
         let mut texture_atlas_builder : TextureAtlasBuilder;
 
-        for (anim_type, vec) in sprite_handles.player_new.iter() {
+        for (clip, vec) in sprite_handles.clips.iter() {
+
+            // frames are numbered in file name order, folders come in any order
+            let mut frames : Vec<Handle<Texture>> = vec.iter().map(|handle| handle.clone_weak().typed::<Texture>()).collect();
+            frames.sort_by_key(|handle| asset_server.get_handle_path(handle).map(|path| path.path().to_path_buf()));
 
             texture_atlas_builder = TextureAtlasBuilder::default();
-            for texture_handle in vec.iter() {
-                let texture = textures.get( texture_handle.clone_weak().typed::<Texture>() ).unwrap();
-                texture_atlas_builder.add_texture( texture_handle.clone_weak().typed::<Texture>(), texture );
+            for texture_handle in frames.iter() {
+                let texture = textures.get( texture_handle.clone_weak() ).unwrap();
+                texture_atlas_builder.add_texture( texture_handle.clone_weak(), texture );
             }
-            let texture_atlas : TextureAtlas = texture_atlas_builder.finish(&mut textures).unwrap();
+            let mut texture_atlas : TextureAtlas = texture_atlas_builder.finish(&mut textures).unwrap();
+            // the builder packs the frames in its own order, put them back in frame order
+            texture_atlas.textures = frames.iter().map(|handle| {
+                texture_atlas.textures[texture_atlas.get_texture_index(handle).unwrap()]
+            }).collect();
+            texture_atlas.texture_handles = Some(frames.into_iter().enumerate().map(|(index, handle)| (handle, index)).collect());
             // add texture atlas to texture atlases resource vector
             let atlas_handle : Handle<TextureAtlas> = texture_atlases.add(texture_atlas);
-            atlas_handles.player.insert(*anim_type, atlas_handle);
+            atlas_handles.clips.insert(clip.clone(), atlas_handle);
         }
 }
 
@@ -1131,6 +1283,7 @@ fn init_world(
 }
 
 
+#[allow(clippy::too_many_arguments)]
 fn init_player(
     mut state: ResMut<State<AppState>>,
     mut commands: Commands,
@@ -1141,11 +1294,13 @@ fn init_player(
     mut static_entities: ResMut<StaticEntities>,
 //    asset_server: Res<AssetServer>,
     win_size: Res<WinSize>,
+    level: Res<level::LevelData>,
+    spawn: Res<SpawnRequest>,
 )   {
         println!("Init player!");
         // I. build texture atlas (sprite sheet) from textures
 
-        let text_handle : Handle<Texture> = sprite_handles.player_new.get(&AnimState::Idle).unwrap()[0].clone_weak().typed::<Texture>();
+        let text_handle : Handle<Texture> = sprite_handles.clips.get(AnimState::Idle.clip()).unwrap()[0].clone_weak().typed::<Texture>();
         // get first sprite texture
         let first_sprite : &Texture = textures.get(text_handle).unwrap();
 
@@ -1170,21 +1325,26 @@ fn init_player(
         let mut half_tile_offset_x: f32 = TILE_GOALSIZE / 2.0;
         let mut half_tile_offset_y: f32 = TILE_GOALSIZE / 2.0;
 
+        // half size of the collider, the sensors are laid out around it
+        let half_size = Vec2::new(desired_with / 2.0, desired_height / 2.0);
+
         // II. spawn player sprite sheet bundle
         let player_id = commands
         .spawn_bundle(PlayerBundle {
             query_marker: Player,
-            health: Health(100.0),
+            health: Health(PLAYER_MAX_HEALTH),
             attack_points: AttackPoints(1.0),
+            allegiance: PLAYER_ALLEGIANCE,
 
             old_current: AnimStateTuple {
                             old: Some(AnimState::Idle), 
-                            current: Some(AnimState::Idle)
+                            current: Some(AnimState::Idle),
+                            replay: false
             },
             sprite_sheet: SpriteSheetBundle {
-                texture_atlas: atlas_handles.player.get(&AnimState::Idle).unwrap().clone(),
+                texture_atlas: atlas_handles.clips.get(AnimState::Idle.clip()).unwrap().clone(),
                 transform: Transform {
-                    translation: Vec3::new( -TILE_UNIT_TRANSLATION*24.0, 0.0, 1.0 ),
+                    translation: level::to_world(spawn.position(&level), 1.0),
                     scale: Vec3::new(effective_scale_x, effective_scale_y, 1.0),
                     ..Default::default()
                 },
@@ -1200,6 +1360,18 @@ fn init_player(
             border_radius : Some(0.0) // optional argument (type Option)
         })
         .insert( RigidBodyEnum::Dynamic )
+        .insert( Layer::Player.collision_layers() )
+        .insert( Contacts::default() )
+        .insert( Controller::default() )
+        .with_children(|parent| {
+            ground::spawn_contact_sensors(parent, half_size);
+            slide::spawn_head_sensor(parent, half_size);
+        })
+        .insert( SlideShape::new(half_size) )
+        .insert( AirAttack::default() )
+        .insert( Rider::default() )
+        .insert( Terrain::default() )
+        .insert( Invincibility::default() )
         .insert(RotationConstraints::lock()) // disallow rotation around axis
         .insert( PhysicMaterial {
             friction: 0.0, 
@@ -1207,8 +1379,8 @@ fn init_player(
             restitution: 0.0,
             ..Default::default()
         })
-        // insert timer as member of PlayerBundle instance
-        .insert(Timer::from_seconds(0.15, true))
+        // plays the clip of the current animState (player_animation)
+        .insert(Animator::new(AnimState::Idle.clip()))
         .insert(TexSize {
             w: (first_sprite.size.width as f32),
             h: (first_sprite.size.height as f32),
@@ -1310,19 +1482,7 @@ fn init_objects(
                 },
                 ..Default::default()
             })
-            
-            .insert( CollisionShape::Cuboid {
-                half_extends: Vec3::new( desired_with / 2.0, desired_height / 2.0, 1.0),
-                border_radius : None // optional argument (type Option)
-            })
-            .insert( RigidBodyEnum::Static )
-            .insert( PhysicMaterial {
-                friction: 0.0,
-                density: 0.0,
-                restitution: 0.0,
-                ..Default::default()
-            })
-            
+            // its deck is a one-way platform from the level data (platform.rs)
             .insert( TexSize {
                 w: width,
                 h: height,
@@ -1335,10 +1495,7 @@ fn init_objects(
         point_br = translation + (Vec3::new( ((width * effective_scale_x) / 2.0), 0.0, 0.0));
         point_br = point_br - (Vec3::new( 0.0, ((height * effective_scale_y) / 2.0), 0.0));
 
-//        [cfg(target_arch = "wasm32")]
-//        points.hash_map.insert( GateIdentifier::Blog, (point_tl, point_br) );
-
-        // pond/lake (no collisions for now)
+        // pond/lake. the water in it is a hazard volume from the level data (hazard.rs)
         commands
             .spawn_bundle( SpriteBundle {
                 material: materials.add( ColorMaterial {
@@ -1372,254 +1529,122 @@ fn init_objects(
 }
 
 
+// picks the player's animation state from the keys. moving and jumping the body is up to
+// the controller (controller.rs).
+#[allow(clippy::type_complexity)]
 fn player_input(
     time: Res<Time>,
-    mut actions: ResMut<ActionDesc>,
-    mut atlas_handles: Res<AtlasHandles>,
     keys: Res<Input<KeyCode>>,
-    mut query: Query<( Option<&Player>, &mut AnimStateTuple, &mut Transform, Option<&mut Velocity>, &mut TextureAtlasSprite, &mut Handle<TextureAtlas> )>,
-//    points: Res<Points>,
-    mut timers: ResMut<Timers>
+    mut query: Query<( &mut AnimStateTuple, &Controller, &Velocity, &AirAttack ), With<Player>>,
+    mut jumped_events: EventReader<JumpedEvent>,
+    mut timers: ResMut<Timers>,
+    dialogue: Res<DialogueState>,
+    shop: Res<ShopState>,
 )   {
-        let mut moving: bool = false;
-        let mut aborted: bool = false;
-
-        for ( player, mut tuple, mut transform, mut velocity, mut sprite, mut handle_atlas ) in query.iter_mut() {
-            let mut vel = velocity.unwrap();
-                // if timers not started or not finished yet.
-            if (timers.anim_jump_timer.elapsed_secs() > 0.0) && !timers.anim_jump_timer.finished() {
-                timers.anim_jump_timer.tick( time.delta() );
-                // TODO; check for attack key just pressed. to initiate jump_attack animation here.
-                // block all movement
-                break;
-            } else if timers.anim_jump_timer.finished() {
-                // switch to 'glide anim' (set atlas handle in player_animation system)
-                tuple.current = Some(AnimState::Glide);
-//                *handle_atlas = atlas_handles.player.get( &tuple.current.unwrap() ).unwrap().clone();
-                let y_move = (vel.linear * Vec3::Y);
-                vel.linear -= y_move;
-                vel.linear += Vec3::new(0.0, -20.0, 0.0);
-                timers.anim_jump_timer.reset();
-                break;
-                // TODO: reset jump_timer on vertical collision in a collision handling system.
-            } else {
+        // player input is suspended while a dialogue or shop is open. stand still and listen.
+        if dialogue.is_open() || shop.is_open() {
+            for ( mut tuple, _, _, _ ) in query.iter_mut() {
+                tuple.current = Some(AnimState::Idle);
             }
+            return;
+        }
+
+        // jumps are started by the controller (buffered, coyote time, ...)
+        let jumped = jumped_events.iter().count() > 0;
 
+        for ( mut tuple, controller, velocity, air_attack ) in query.iter_mut() {
             if (timers.anim_attack_timer.elapsed_secs() > 0.0) && !timers.anim_attack_timer.finished() {
                 timers.anim_attack_timer.tick( time.delta() );
                 break;
             } else if timers.anim_attack_timer.finished() {
-                // reset timer, and continue as usual.
+                // reset timer, and continue as usual. the swing is over, stand (or run, below)
+                // instead of staying on its last frame.
                 timers.anim_attack_timer.reset();
+                if tuple.current == Some(AnimState::Attack) {
+                    tuple.current = Some(AnimState::Idle);
+                }
             } else {
             }
-            
-            if let Some(result) = player {
-                if keys.just_pressed(KeyCode::A) {
-                    println!("just pressed A!");
-                }
-                if keys.just_pressed(KeyCode::D) {
-                    println!("just pressed D!");
-                }
-                if keys.just_pressed(KeyCode::W) {
-                    println!("just pressed W!");
-                }
-                if keys.just_pressed(KeyCode::S) {
-                    println!("just pressed S!");
-                }
-
-                if (
-                    keys.pressed(KeyCode::A) || keys.pressed(KeyCode::D) || keys.pressed(KeyCode::W) || keys.pressed(KeyCode::S) ) {
-                        tuple.current = Some(AnimState::Run);
-                        moving = true;
-                } else if (
-                        ( keys.just_released(KeyCode::A) || keys.just_released(KeyCode::D) || keys.just_released(KeyCode::W) || keys.just_released(KeyCode::S) )
-                    &&
-                        ( !keys.pressed(KeyCode::Space) || !keys.pressed(KeyCode::LShift) )
-                ) {
-                        tuple.current = Some(AnimState::Idle);
-                        moving = false;
-                } else {
-                }
 
-                // keys just pressed take precedence in animation state over those recently pressed.
-                if keys.just_pressed(KeyCode::LShift) {
-                    tuple.current = Some(AnimState::Jump);
-                    timers.anim_jump_timer.tick( time.delta() );
-//                    *handle_atlas = atlas_handles.player.get( &tuple.current.unwrap() ).unwrap().clone();
-                    vel.linear += Vec3::new(0.0, 70.0, 0.0);
+            if jumped {
+                tuple.current = Some(AnimState::Jump);
+                break;
+            }
+            match controller.state {
+                // hanging on, sliding down or climbing a wall
+                MoveState::WallSlide | MoveState::Climb => {
+                    tuple.current = Some(AnimState::Climb);
                     break;
-                } else if keys.just_released(KeyCode::LShift) {
                 }
-
-                if keys.just_pressed(KeyCode::Space) {
-                    tuple.current = Some(AnimState::Attack);
-                    timers.anim_attack_timer.tick( time.delta() );
-//                    *handle_atlas = atlas_handles.player.get( &tuple.current.unwrap() ).unwrap().clone();
-                    vel.linear = Vec3::new(0.0, 0.0, 0.0);
+                MoveState::Slide => {
+                    tuple.current = Some(AnimState::Slide);
                     break;
-                } else if keys.just_released(KeyCode::Space) {
                 }
-
-                // just moving, or idle.
-                *handle_atlas = atlas_handles.player.get( &tuple.current.unwrap() ).unwrap().clone();
-/* 
-                if (*anim_state == AnimState::Idle || ((*anim_state == AnimState::Attack) && !moving) ) {
+                // in the air: jump while rising, glide while falling, until landing_handler ends it.
+                // air attacks (air_attack.rs) take precedence.
+                MoveState::Air => {
+                    tuple.current = Some(match air_attack.kind() {
+                        Some(kind) => kind.anim_state(),
+                        None if velocity.linear.y > 0.0 => AnimState::Jump,
+                        None => AnimState::Glide,
+                    });
                     break;
                 }
-*/
-                if keys.pressed(KeyCode::A) && keys.just_pressed(KeyCode::A) {
-                    // just pressed. has been held for 1 or 2 frames only.
-                    if !( vel.linear * Vec3::X <= Vec3::new(-100.0, 0.0, 0.0) ) {
-                        vel.linear -= Vec3::new(120.0, 0.0, 0.0);
-                    }
-//                    actions.move_dir = Some(MovementDir::MoveLeft);
-                    if !sprite.flip_x {
-                        sprite.flip_x = true;
-                    }
-                    println!("just pressed and held down!");
-
-                } else if keys.pressed(KeyCode::A) && !keys.just_pressed(KeyCode::A) {
-                    // has been pressed for more than 1 (or 2 ) frames, increase 
-                    if !( vel.linear * Vec3::X <= Vec3::new(-100.0, 0.0, 0.0) ) {
-                        vel.linear -= Vec3::new(120.0, 0.0, 0.0);
-                    }
-
-                    println!("held down, but not just pressed!");
-                } else if keys.just_released(KeyCode::A) {
-                    // just released
-                    if !( vel.linear * Vec3::X >= Vec3::new(0.0, 0.0, 0.0) ) {
-                        // decrease lateral velocity to 0.0
-                        vel.linear = vel.linear + (vel.linear * Vec3::X).abs();
-                    }
-                    println!("just released!");
-                } else {
-                    // ...
-                }
-
-                if keys.pressed(KeyCode::D) && keys.just_pressed(KeyCode::D) {
-                    // just pressed. has been held for 1 or 2 frames only.
-                    if !( vel.linear * Vec3::X >= Vec3::new(100.0, 0.0, 0.0) ) {
-                        vel.linear += Vec3::new(120.0, 0.0, 0.0);
-                    }
-
-                    if sprite.flip_x {
-                        sprite.flip_x = false;
-                    }
-                } else if keys.pressed(KeyCode::D) && !keys.just_pressed(KeyCode::D) {
-                    // has been pressed for more than 1 (or 2 ) frames, increase 
-                    if !( vel.linear * Vec3::X >= Vec3::new(100.0, 0.0, 0.0) ) {
-                        vel.linear += Vec3::new(120.0, 0.0, 0.0);
-                    }
-                } else if keys.just_released(KeyCode::D) {
-                    // just released
-                    if !( vel.linear * Vec3::X <= Vec3::new(0.0, 0.0, 0.0) ) {
-                        // decrease lateral velocity to 0.0
-                        vel.linear = vel.linear - (vel.linear * Vec3::X).abs();
-                    }
-                } else {
-                    // ...
-                }
-
-                if keys.pressed(KeyCode::W) && keys.just_pressed(KeyCode::W) {
-                    // just pressed. has been held for 1 or 2 frames only.
-                    if !( vel.linear * Vec3::Y >= Vec3::new(0.0, 100.0, 0.0) ) {
-                        vel.linear += Vec3::new(0.0, 100.0, 0.0);
-                    }
-                } else if keys.pressed(KeyCode::W) && !keys.just_pressed(KeyCode::W) {
-                    // has been pressed for more than 1 (or 2 ) frames, increase 
-                    if !( vel.linear * Vec3::Y >= Vec3::new(0.0, 100.0, 0.0) ) {
-                        vel.linear += Vec3::new(0.0, 100.0, 0.0);
-                    }
-                } else if keys.just_released(KeyCode::W) {
-                    // just released
-                    if !( vel.linear * Vec3::Y <= Vec3::new(0.0, 0.0, 0.0) ) {
-                        // decrease lateral velocity to 0.0
-                        vel.linear = vel.linear - (vel.linear * Vec3::Y).abs();
+                MoveState::Ground => {
+                    // climbed down to the ground, or stood up from a slide
+                    if tuple.current == Some(AnimState::Climb) || tuple.current == Some(AnimState::Slide) {
+                        tuple.current = Some(AnimState::Idle);
                     }
-                } else {
                 }
+            }
 
-                if keys.pressed(KeyCode::S) && keys.just_pressed(KeyCode::S) {
-                    // just pressed. has been held for 1 or 2 frames only.
-                    if !( vel.linear * Vec3::Y <= Vec3::new(0.0, -100.0, 0.0) ) {
-                        vel.linear -= Vec3::new(0.0, 100.0, 0.0);
-                    }
-                } else if keys.pressed(KeyCode::S) && !keys.just_pressed(KeyCode::S) {
-                    // has been pressed for more than 1 (or 2 ) frames, increase 
-                    if !( vel.linear * Vec3::Y <= Vec3::new(0.0, -100.0, 0.0) ) {
-                        vel.linear -= Vec3::new(0.0, 100.0, 0.0);
-                    }
-                } else if keys.just_released(KeyCode::S) {
-                    // just released
-                    if !( vel.linear * Vec3::Y >= Vec3::new(0.0, 0.0, 0.0) ) {
-                        // decrease lateral velocity to 0.0
-                        vel.linear = vel.linear + (vel.linear * Vec3::Y).abs();
-                    }
-                }
+            if keys.pressed(KeyCode::A) || keys.pressed(KeyCode::D) {
+                tuple.current = Some(AnimState::Run);
+            } else if keys.just_released(KeyCode::A) || keys.just_released(KeyCode::D) {
+                tuple.current = Some(AnimState::Idle);
+            } else {
             }
-        }
 
-/* 
-        if (timers.gate_timer.elapsed_secs() % 4.0) >= 2.0 {
-            unsafe {
-                if (PAGE_ID != GateIdentifier::None) {
-                    PAGE_ID = GateIdentifier::None;
-                    println!("Timer finished. setting Gateidentifier to none!");
-                    println!("{}", format!("elasped secs: {}", timers.gate_timer.elapsed_secs()));
-                    //timers.gate_timer.tick( Duration::from_secs(2) ); // or reset timer?
-                    timers.gate_timer.reset();
-                }
+            if keys.just_pressed(KeyCode::Space) {
+                tuple.replay = tuple.old == Some(AnimState::Attack);
+                tuple.current = Some(AnimState::Attack);
+                timers.anim_attack_timer.tick( time.delta() );
+                break;
             }
         }
-*/
-        // (v.is_empty == true; but no effect on reserved capacity) 
 }
 
-fn camera_handler(
-    mut query: Query<(&OrthographicProjection, &mut Velocity)>, // for camera
-    mut set: QuerySet<(
-        Query<&Transform, With<Player>>,
-        Query<&Transform, With<Camera2d>>
-    )>,
-    mut static_entities: ResMut<StaticEntities>,
-)   {
-        if let Ok((ortho, mut velocity)) =
-            query.get_mut(*static_entities.handles.get(&StaticEntityId::Camera2d).unwrap()) {
-
-            if let Ok((player_transform)) = 
-                set.q0().get(*static_entities.handles.get(&StaticEntityId::Player).unwrap()) {
-
-                if let Ok((cam_transform)) = 
-                    set.q1().get(*static_entities.handles.get(&StaticEntityId::Camera2d).unwrap()) {
-                    // move camera right
-                    if (player_transform.translation * Vec3::X) >
-                        (cam_transform.translation * Vec3::X + Vec3::new(ortho.right - 50.0, 0.0, 0.0).abs()) {
-                                velocity.linear = Vec3::new(140.0, 0.0, 0.0);
-                    // move camera left
-                    } else if (player_transform.translation * Vec3::X) <
-                        (cam_transform.translation * Vec3::X - Vec3::new(ortho.left + 50.0, 0.0, 0.0).abs()) {
-                            velocity.linear = Vec3::new(-140.0, 0.0, 0.0);
-                    }
+// add up points for collected items and show them in the top bar.
+fn score_handler(
+    mut events: EventReader<PickupEvent>,
+    mut score: ResMut<Score>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    for event in events.iter() {
+        score.0 += event.kind.score_value() * event.count;
+    }
+    if score.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[0].value = format!("Score: {}", score.0);
+        }
+    }
+}
 
-                        // camera is adjusting. determine whether to stop
-                    if velocity.linear * Vec3::X > Vec3::ZERO {
-                        // camera has reached horizontal center. stop.
-                        if cam_transform.translation * Vec3::X >= player_transform.translation * Vec3::X {
-                                velocity.linear = Vec3::ZERO;
-                        }
-                    } else if velocity.linear * Vec3::X < Vec3::ZERO {
-                        // cameras has reached horizontal center. stop.
-                        if cam_transform.translation * Vec3::X <= player_transform.translation * Vec3::X {
-                            velocity.linear = Vec3::ZERO;
-                        }
-                    }
-                }
+fn teleport_handler(
+    mut events: EventReader<TeleportEvent>,
+    static_entities: Res<StaticEntities>,
+    mut query: Query<(&mut Transform, &mut Velocity), With<Player>>,
+) {
+    for event in events.iter() {
+        if let Some(player) = static_entities.handles.get(&StaticEntityId::Player) {
+            if let Ok((mut transform, mut velocity)) = query.get_mut(*player) {
+                transform.translation = level::to_world(event.pos, transform.translation.z);
+                velocity.linear = Vec3::ZERO;
             }
         }
+    }
 }
 
-
 // spawn, despawn regular enemies and bosses (modified in collision_handler, player_input)
 fn enemy_handler(
     mut static_entities: ResMut<StaticEntities>,
@@ -1655,49 +1680,51 @@ fn projectile_handler(
 }
 
 // determine animation state, health points of entities, etc.
+// branches on the physics layers of both sides. sensors and pickups are handled in their
+// own modules (gate, trigger, inventory), ground and wall contacts in ground.rs.
 fn collision_handler(
-    time: Res<Time>,
-    mut timers: ResMut<Timers>,
     mut events: EventReader<CollisionEvent>,
-    mut query: Query<(Entity, Option<&Player>, &mut AnimStateTuple, &mut Velocity)>,
+    attackers: Query<(&AttackPoints, &CreatureInfo, &Allegiance, &Transform)>,
+    mut damage_events: EventWriter<DamageEvent>,
 )   {
         for event in events.iter() {
-            match event {
-                CollisionEvent::Started(d1, d2) => {
-                    for (entity, player, mut tuple, mut velocity) in query.iter_mut() {
-                        if let Some(player) = player {
-                            if d1.rigid_body_entity().id() == entity.id() {
-                                // TODO: check if collision is vertical (downwards)
-                                // if yes, check if collision target is rigid body.
-                                timers.anim_jump_timer.reset();
-                                if let Some(current) = tuple.current {
-                                    if current == AnimState::Glide || current == AnimState::Jump {
-                                        tuple.old = Some(current);
-                                        // reset to Idle, or 'Fall' animation, if exists...
-                                        tuple.current = Some(AnimState::Idle);
-                                    }
-                                }
-                            } else if d2.rigid_body_entity().id() == entity.id() {
-                                // same as above ...
-                                timers.anim_jump_timer.reset();
-                                if let Some(current) = tuple.current {
-                                    if current == AnimState::Glide || current == AnimState::Jump {
-                                        tuple.old = Some(current);
-                                        // reset to Idle, or 'Fall' animation, if exists...
-                                        tuple.current = Some(AnimState::Idle);
-                                    }
-                                }
-                            } else {
-                                // noone of the colliding entities are the player.
-                                // -> TODO: handle collision.
-                            }
-                        }
+            if let CollisionEvent::Started(d1, d2) = event {
+                let (player, other) = match (Layer::of(d1), Layer::of(d2)) {
+                    (Some(Layer::Player), _) => (d1, d2),
+                    (_, Some(Layer::Player)) => (d2, d1),
+                    // kunais hitting enemies or the world are handled in air_attack.rs
+                    _ => continue,
+                };
+
+                // touching an enemy hurts, allies don't collide with the player at all
+                if Layer::of(other) == Some(Layer::Enemies) {
+                    if let Ok((attack_points, info, allegiance, transform)) = attackers.get(other.rigid_body_entity()) {
+                        damage_events.send(DamageEvent {
+                            target: player.rigid_body_entity(),
+                            amount: attack_points.0,
+                            attacker: AttackerKind::Creature(info.kind),
+                            allegiance: *allegiance,
+                            source: Some(transform.translation.truncate()),
+                        });
                     }
-
-
                 }
-                CollisionEvent::Stopped(d1, d2) => {
-                
+            }
+        }
+}
+
+// the player's feet touched the ground (see ground.rs). ends jumps and glides.
+fn landing_handler(
+    mut events: EventReader<LandedEvent>,
+    mut players: Query<&mut AnimStateTuple, With<Player>>,
+)   {
+        for event in events.iter() {
+            if let Ok(mut tuple) = players.get_mut(event.entity) {
+                if let Some(current) = tuple.current {
+                    if matches!(current, AnimState::Glide | AnimState::Jump | AnimState::Climb | AnimState::JumpAttack | AnimState::JumpThrow) {
+                        tuple.old = Some(current);
+                        // reset to Idle, or 'Fall' animation, if exists...
+                        tuple.current = Some(AnimState::Idle);
+                    }
                 }
             }
         }
@@ -0,0 +1,193 @@
+// non player characters standing around in the level. they idle, turn towards the
+// player and start their dialogue when the player presses the interact key nearby.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::Animator;
+use crate::dialogue::{DialogueState, StartDialogueEvent};
+use crate::level::{self, LevelData};
+use crate::shop::ShopState;
+use crate::{player_transform, AppState, Player, StaticEntities, KEY_INTERACT, TILE_GOALSIZE};
+
+// npc sprites are drawn at twice their texture size (roughly the player's height)
+const NPC_SCALE: f32 = 2.0;
+// breathing: vertical squash of the idle pose
+const NPC_BREATH_SPEED: f32 = 2.0;
+const NPC_BREATH_AMOUNT: f32 = 0.03;
+const DEFAULT_RADIUS: f32 = TILE_GOALSIZE * 4.0;
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NpcKind {
+    Gabe,
+    HatGuy,
+    Mani,
+    Sensei,
+    Vendor,
+}
+
+impl NpcKind {
+    pub fn texture_path(self) -> &'static str {
+        match self {
+            NpcKind::Gabe => "textures/rpg/chars/gabe/gabe-idle-run.png",
+            NpcKind::HatGuy => "textures/rpg/chars/hat-guy/hat-guy.png",
+            NpcKind::Mani => "textures/rpg/chars/mani/mani-idle-run.png",
+            NpcKind::Sensei => "textures/rpg/chars/sensei/sensei.png",
+            NpcKind::Vendor => "textures/rpg/chars/vendor/generic-rpg-vendor.png",
+        }
+    }
+
+    // (frame size, frames in the sheet)
+    fn sheet(self) -> (Vec2, usize) {
+        match self {
+            NpcKind::Gabe | NpcKind::Mani => (Vec2::new(24.0, 24.0), 7),
+            NpcKind::HatGuy => (Vec2::new(16.0, 22.0), 1),
+            NpcKind::Sensei => (Vec2::new(16.0, 23.0), 1),
+            NpcKind::Vendor => (Vec2::new(56.0, 51.0), 1),
+        }
+    }
+
+    // clip in assets/data/animations.ron, played on the sheet
+    fn idle_clip(self) -> &'static str {
+        match self {
+            NpcKind::Gabe => "gabe/idle",
+            NpcKind::HatGuy => "hat_guy/idle",
+            NpcKind::Mani => "mani/idle",
+            NpcKind::Sensei => "sensei/idle",
+            NpcKind::Vendor => "vendor/idle",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NpcKind::Gabe => "Gabe",
+            NpcKind::HatGuy => "Hat Guy",
+            NpcKind::Mani => "Mani",
+            NpcKind::Sensei => "Sensei",
+            NpcKind::Vendor => "Vendor",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Npc {
+    pub kind: NpcKind,
+    // dialogue tree started when talking to this npc
+    pub dialogue: String,
+    // how close the player has to be to talk
+    pub radius: f32,
+}
+
+#[derive(Debug)]
+struct NpcIdle {
+    // offset into the breathing cycle, so npcs don't breathe in sync
+    phase: f32,
+}
+
+pub struct NpcPlugin;
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Setup)
+                .with_system(init_npcs.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::Ready)
+                .with_system(npc_animation.system())
+                // before the dialogue, so the key press closing a dialogue doesn't start it again
+                .with_system(npc_interact.system().before("dialogue_start")),
+        );
+    }
+}
+
+fn init_npcs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    level: Res<LevelData>,
+) {
+    for (index, desc) in level.npcs.iter().enumerate() {
+        let (frame_size, frames) = desc.kind.sheet();
+        let atlas = TextureAtlas::from_grid(
+            asset_server.load(desc.kind.texture_path()),
+            frame_size,
+            frames,
+            1,
+        );
+
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: texture_atlases.add(atlas),
+                transform: Transform {
+                    translation: level::to_world(desc.pos, 1.0),
+                    scale: Vec3::new(NPC_SCALE, NPC_SCALE, 1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Npc {
+                kind: desc.kind,
+                dialogue: desc.dialogue.clone(),
+                radius: desc.radius.unwrap_or(DEFAULT_RADIUS),
+            })
+            .insert(NpcIdle {
+                phase: index as f32 * 1.7,
+            })
+            .insert(Animator::new(desc.kind.idle_clip()));
+    }
+}
+
+fn npc_animation(
+    time: Res<Time>,
+    static_entities: Res<StaticEntities>,
+    players: Query<&Transform, With<Player>>,
+    mut query: Query<(&Npc, &NpcIdle, &mut TextureAtlasSprite, &mut Transform), Without<Player>>,
+) {
+    let player_x = player_transform(&static_entities, &players).map(|transform| transform.translation.x);
+
+    // the frames are up to the Animator, this breathes and turns
+    for (npc, idle, mut sprite, mut transform) in query.iter_mut() {
+        let breath = ((time.seconds_since_startup() as f32) * NPC_BREATH_SPEED + idle.phase).sin();
+        transform.scale.y = NPC_SCALE * (1.0 + breath * NPC_BREATH_AMOUNT);
+
+        // look at the player when they come close
+        if let Some(player_x) = player_x {
+            if (player_x - transform.translation.x).abs() < npc.radius {
+                sprite.flip_x = player_x < transform.translation.x;
+            }
+        }
+    }
+}
+
+fn npc_interact(
+    keys: Res<Input<KeyCode>>,
+    dialogue: Res<DialogueState>,
+    shop: Res<ShopState>,
+    static_entities: Res<StaticEntities>,
+    players: Query<&Transform, With<Player>>,
+    npcs: Query<(&Npc, &Transform), Without<Player>>,
+    mut start_events: EventWriter<StartDialogueEvent>,
+) {
+    if !keys.just_pressed(KEY_INTERACT) || dialogue.is_open() || shop.is_open() {
+        return;
+    }
+    let player_position = match player_transform(&static_entities, &players) {
+        Some(transform) => transform.translation.truncate(),
+        None => return,
+    };
+
+    // talk to the closest npc in range
+    let closest = npcs
+        .iter()
+        .map(|(npc, transform)| (npc, transform.translation.truncate().distance(player_position)))
+        .filter(|(npc, distance)| *distance <= npc.radius)
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    if let Some((npc, _)) = closest {
+        start_events.send(StartDialogueEvent {
+            tree: npc.dialogue.clone(),
+            npc: npc.kind,
+        });
+    }
+}
@@ -0,0 +1,235 @@
+// solid ground from the level data: the floor, the walls at the level's edges and the
+// ledges leading up to the gates. they are on the world physics layer, so the player
+// stands on them and the ground sensors (ground.rs) pick them up.
+//
+// platforms from the level data are one-way: the player jumps up through them, lands on
+// top and drops through with S and jump. platforms with a path move along it on kinematic
+// bodies and carry whoever stands on them. the physics engine can't collide from one side
+// only, so landing on them is worked out here and the player's body never touches them.
+
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::controller::Controller;
+use crate::ground::{Contacts, LandedEvent};
+use crate::level::{self, LevelData};
+use crate::{AppState, Layer, TILE_UNIT_TRANSLATION};
+
+const SOLID_COLOR: Color = Color::rgb(0.45, 0.32, 0.2);
+const CLIMBABLE_COLOR: Color = Color::rgb(0.25, 0.5, 0.2);
+const PLATFORM_COLOR: Color = Color::rgb(0.62, 0.46, 0.26);
+
+// pixels the feet may be below a platform's top and still land on it
+const LAND_TOLERANCE: f32 = 2.0;
+// pixels below a platform's top before the player can land on it again after dropping through
+const DROP_CLEARANCE: f32 = 4.0;
+
+#[derive(Debug)]
+pub struct Solid;
+
+// the sides of this solid can be climbed
+#[derive(Debug)]
+pub struct Climbable;
+
+#[derive(Debug)]
+pub struct Platform {
+    half_size: Vec2,
+}
+
+// moves its platform through the waypoints (world positions), over and over
+#[derive(Debug)]
+struct Mover {
+    waypoints: Vec<Vec2>,
+    next: usize,
+    // pixels per second
+    speed: f32,
+}
+
+// lets a body stand on platforms
+#[derive(Default, Debug)]
+pub struct Rider {
+    platform: Option<Entity>,
+    // dropping through this platform
+    dropping: Option<Entity>,
+    // height of the feet last frame
+    feet: Option<f32>,
+}
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Setup)
+                .with_system(init_solids.system())
+                .with_system(init_platforms.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::Ready)
+                .with_system(platform_handler.system().label("platforms").after("contacts").before("controller")),
+        );
+    }
+}
+
+fn init_solids(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    level: Res<LevelData>,
+) {
+    let material = materials.add(SOLID_COLOR.into());
+    let climbable_material = materials.add(CLIMBABLE_COLOR.into());
+    for desc in level.solids.iter() {
+        let size = Vec2::new(desc.size.0, desc.size.1) * TILE_UNIT_TRANSLATION;
+        let mut solid = commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite::new(size),
+            material: if desc.climbable {
+                climbable_material.clone()
+            } else {
+                material.clone()
+            },
+            transform: Transform::from_translation(level::to_world(desc.pos, 1.0)),
+            ..Default::default()
+        });
+        if desc.climbable {
+            solid.insert(Climbable);
+        }
+        solid
+            .insert(Solid)
+            .insert(RigidBody::Static)
+            .insert(Layer::World.collision_layers())
+            .insert(CollisionShape::Cuboid {
+                half_extends: (size / 2.0).extend(1.0),
+                border_radius: None,
+            })
+            .insert(PhysicMaterial {
+                friction: 0.0,
+                restitution: 0.0,
+                ..Default::default()
+            });
+    }
+}
+
+fn init_platforms(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    level: Res<LevelData>,
+) {
+    let material = materials.add(PLATFORM_COLOR.into());
+    for desc in level.platforms.iter() {
+        let size = Vec2::new(desc.size.0, desc.size.1) * TILE_UNIT_TRANSLATION;
+        let mut platform = commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite::new(size),
+            material: material.clone(),
+            transform: Transform::from_translation(level::to_world(desc.pos, 1.0)),
+            ..Default::default()
+        });
+        if !desc.path.is_empty() {
+            let waypoints = std::iter::once(desc.pos)
+                .chain(desc.path.iter().copied())
+                .map(|pos| level::to_world(pos, 0.0).truncate())
+                .collect();
+            platform.insert(Mover {
+                waypoints,
+                next: 1,
+                speed: desc.speed * TILE_UNIT_TRANSLATION,
+            });
+        }
+        // only kunais collide with the body
+        platform
+            .insert(Platform { half_size: size / 2.0 })
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Layer::Platforms.collision_layers())
+            .insert(CollisionShape::Cuboid {
+                half_extends: (size / 2.0).extend(1.0),
+                border_radius: None,
+            });
+    }
+}
+
+// moves the platforms, then lands, carries and drops the riders. runs before the controller,
+// so it knows whether the player stands on something.
+#[allow(clippy::type_complexity)]
+fn platform_handler(
+    time: Res<Time>,
+    mut platforms: Query<(Entity, &Platform, Option<&mut Mover>, &mut Transform)>,
+    mut riders: Query<
+        (Entity, &mut Rider, &mut Contacts, &mut Controller, &mut Velocity, &CollisionShape, &mut Transform),
+        Without<Platform>,
+    >,
+    mut landed_events: EventWriter<LandedEvent>,
+) {
+    // (platform, center, half size, movement this frame)
+    let mut moved = Vec::new();
+    for (entity, platform, mover, mut transform) in platforms.iter_mut() {
+        let position = transform.translation.truncate();
+        let mut delta = Vec2::ZERO;
+        if let Some(mut mover) = mover {
+            let to_target = mover.waypoints[mover.next] - position;
+            let step = mover.speed * time.delta_seconds();
+            if to_target.length() <= step {
+                delta = to_target;
+                mover.next = (mover.next + 1) % mover.waypoints.len();
+            } else {
+                delta = to_target.normalize() * step;
+            }
+            transform.translation += delta.extend(0.0);
+        }
+        moved.push((entity, position + delta, platform.half_size, delta));
+    }
+
+    for (entity, mut rider, mut contacts, mut controller, mut velocity, shape, mut transform) in riders.iter_mut() {
+        let half_extends = match shape {
+            CollisionShape::Cuboid { half_extends, .. } => *half_extends,
+            _ => continue,
+        };
+        let feet = transform.translation.y - half_extends.y;
+        let x = transform.translation.x;
+        let overlaps = |center: Vec2, half_size: Vec2| (x - center.x).abs() < half_size.x + half_extends.x;
+
+        if controller.drop_through {
+            controller.drop_through = false;
+            rider.dropping = rider.platform.take();
+        }
+        if let Some(dropping) = rider.dropping {
+            let clear = match moved.iter().find(|(platform, ..)| *platform == dropping) {
+                Some((_, center, half_size, _)) => {
+                    feet < center.y + half_size.y - DROP_CLEARANCE || !overlaps(*center, *half_size)
+                }
+                None => true,
+            };
+            if clear {
+                rider.dropping = None;
+            }
+        }
+
+        // stay on last frame's platform, or land on one the feet went through since.
+        // never while moving up, so jumps pass through from below.
+        let falling = velocity.linear.y <= 0.0;
+        let standing = moved.iter().find(|(platform, center, half_size, delta)| {
+            if !falling || !overlaps(*center, *half_size) || rider.dropping == Some(*platform) {
+                return false;
+            }
+            let top = center.y + half_size.y;
+            rider.platform == Some(*platform)
+                || (matches!(rider.feet, Some(last) if last >= top - delta.y - LAND_TOLERANCE)
+                    && feet <= top + LAND_TOLERANCE)
+        });
+
+        match standing {
+            Some((platform, center, half_size, delta)) => {
+                transform.translation.x += delta.x;
+                transform.translation.y = center.y + half_size.y + half_extends.y;
+                if velocity.linear.y != 0.0 {
+                    velocity.linear.y = 0.0;
+                }
+                rider.platform = Some(*platform);
+            }
+            None => rider.platform = None,
+        }
+        rider.feet = Some(transform.translation.y - half_extends.y);
+
+        if contacts.set_on_platform(rider.platform.is_some()) {
+            landed_events.send(LandedEvent { entity });
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// quests from assets/data/quests.ron. objectives advance on the events other systems raise
+// (defeated creatures, pickups, dialogues, chests, trigger zones). progress is kept in the
+// save data and listed in the quest tracker on the right side of the screen. opened chests,
+// held items and creatures defeated earlier in the session count towards a quest when it
+// starts, the level may not have enough left otherwise.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::chest::ChestOpenedEvent;
+use crate::creature::{CreatureDefeatedEvent, CreatureKind};
+use crate::dialogue::{DialogueAction, DialogueEvent};
+use crate::inventory::{Inventory, ItemKind, PickupEvent};
+use crate::npc::NpcKind;
+use crate::save::SaveData;
+use crate::trigger::TriggerEvent;
+use crate::AppState;
+
+const QUEST_SOURCE: &str = include_str!("../assets/data/quests.ron");
+
+const TRACKER_WIDTH: f32 = 260.0;
+const TITLE_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+const OBJECTIVE_COLOR: Color = Color::WHITE;
+const OBJECTIVE_DONE_COLOR: Color = Color::rgb(0.55, 0.8, 0.55);
+
+#[derive(Deserialize)]
+pub struct QuestDesc {
+    pub id: String,
+    pub title: String,
+    // started as soon as the game runs, instead of from a dialogue
+    #[serde(default)]
+    pub auto_start: bool,
+    pub objectives: Vec<Objective>,
+    #[serde(default)]
+    pub reward: Vec<(ItemKind, u32)>,
+}
+
+#[derive(Deserialize)]
+pub struct Objective {
+    pub goal: Goal,
+    #[serde(default = "one")]
+    pub count: u32,
+    // shown in the tracker
+    pub text: String,
+}
+
+fn one() -> u32 {
+    1
+}
+
+// something that happened in the game, objectives count matching ones.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub enum Goal {
+    Defeat(CreatureKind),
+    Collect(ItemKind),
+    Talk(NpcKind),
+    // chest id from level.ron
+    OpenChest(String),
+    // trigger id from level.ron
+    Reach(String),
+}
+
+// per quest state, stored in the save data
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct QuestProgress {
+    // one counter per objective
+    pub counts: Vec<u32>,
+    pub completed: bool,
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+pub struct Quests(pub Vec<QuestDesc>);
+
+impl Quests {
+    pub fn load() -> Self {
+        Quests(ron::de::from_str(QUEST_SOURCE).expect("assets/data/quests.ron is malformed"))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&QuestDesc> {
+        self.0.iter().find(|quest| quest.id == id)
+    }
+}
+
+struct QuestFont(Handle<Font>);
+
+// creatures the player has defeated this session. defeated creatures stay gone until the
+// game is started again (they respawn from level.ron), so quests started later count them
+// too. not saved, kills from earlier sessions don't count.
+#[derive(Default, Debug)]
+struct Defeated(HashMap<CreatureKind, u32>);
+
+////////////////////
+// Resources end //
+////////////////////
+
+#[derive(Debug, Clone)]
+pub enum QuestEvent {
+    Started { id: String },
+    Completed { id: String },
+}
+
+#[derive(Debug)]
+struct QuestTrackerText;
+
+pub struct QuestPlugin;
+
+impl Plugin for QuestPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(Quests::load())
+            .init_resource::<Defeated>()
+            .add_event::<QuestEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_quest_tracker.system())
+                    .with_system(quest_auto_start.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(quest_start.system().label("quest_start"))
+                    .with_system(quest_progress.system().label("quest_progress").after("quest_start"))
+                    .with_system(quest_tracker_update.system().after("quest_progress")),
+            );
+    }
+}
+
+// how far an objective is along before its quest starts
+fn initial_count(objective: &Objective, save: &SaveData, inventory: &Inventory, defeated: &Defeated) -> u32 {
+    let count = match &objective.goal {
+        Goal::Defeat(kind) => defeated.0.get(kind).copied().unwrap_or(0),
+        Goal::Collect(kind) => inventory.count(*kind),
+        Goal::OpenChest(id) => save.opened_chests.contains(id) as u32,
+        // npcs can be talked to and places reached again
+        Goal::Talk(_) | Goal::Reach(_) => 0,
+    };
+    count.min(objective.count)
+}
+
+fn start_quest(
+    id: &str,
+    quests: &Quests,
+    save: &mut SaveData,
+    inventory: &Inventory,
+    defeated: &Defeated,
+    events: &mut EventWriter<QuestEvent>,
+) {
+    let quest = match quests.get(id) {
+        Some(quest) => quest,
+        None => {
+            println!("Unknown quest '{}'", id);
+            return;
+        }
+    };
+    // started or finished earlier
+    if save.quests.contains_key(id) {
+        return;
+    }
+    let counts = quest
+        .objectives
+        .iter()
+        .map(|objective| initial_count(objective, save, inventory, defeated))
+        .collect();
+    save.quests.insert(
+        id.to_string(),
+        QuestProgress {
+            counts,
+            completed: false,
+        },
+    );
+    events.send(QuestEvent::Started { id: id.to_string() });
+}
+
+fn init_quest_tracker(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    // right side of the screen, below the top bar
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(10.0),
+                    top: Val::Px(60.0),
+                    ..Default::default()
+                },
+                max_size: Size::new(Val::Px(TRACKER_WIDTH), Val::Undefined),
+                ..Default::default()
+            },
+            text: Text::default(),
+            ..Default::default()
+        })
+        .insert(QuestTrackerText);
+
+    commands.insert_resource(QuestFont(font));
+}
+
+fn quest_auto_start(
+    quests: Res<Quests>,
+    mut save: ResMut<SaveData>,
+    inventory: Res<Inventory>,
+    defeated: Res<Defeated>,
+    mut events: EventWriter<QuestEvent>,
+) {
+    for quest in quests.0.iter().filter(|quest| quest.auto_start) {
+        if !save.quests.contains_key(&quest.id) {
+            start_quest(&quest.id, &quests, &mut save, &inventory, &defeated, &mut events);
+        }
+    }
+}
+
+// quest givers hand out quests through dialogue choices (DialogueAction::StartQuest)
+fn quest_start(
+    mut dialogue_events: EventReader<DialogueEvent>,
+    quests: Res<Quests>,
+    mut save: ResMut<SaveData>,
+    inventory: Res<Inventory>,
+    defeated: Res<Defeated>,
+    mut events: EventWriter<QuestEvent>,
+) {
+    for event in dialogue_events.iter() {
+        if let DialogueEvent::Action {
+            action: DialogueAction::StartQuest(id),
+            ..
+        } = event
+        {
+            start_quest(id, &quests, &mut save, &inventory, &defeated, &mut events);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn quest_progress(
+    mut pickup_events: EventReader<PickupEvent>,
+    mut defeated_events: EventReader<CreatureDefeatedEvent>,
+    mut dialogue_events: EventReader<DialogueEvent>,
+    mut chest_events: EventReader<ChestOpenedEvent>,
+    mut trigger_events: EventReader<TriggerEvent>,
+    quests: Res<Quests>,
+    mut save: ResMut<SaveData>,
+    mut inventory: ResMut<Inventory>,
+    mut defeated: ResMut<Defeated>,
+    mut events: EventWriter<QuestEvent>,
+) {
+    // (what happened, how often)
+    let mut happened: Vec<(Goal, u32)> = Vec::new();
+    happened.extend(pickup_events.iter().map(|event| (Goal::Collect(event.kind), event.count)));
+    // creatures that fell to another faction don't count
+    for event in defeated_events.iter().filter(|event| event.by_player) {
+        *defeated.0.entry(event.kind).or_insert(0) += 1;
+        happened.push((Goal::Defeat(event.kind), 1));
+    }
+    happened.extend(dialogue_events.iter().filter_map(|event| match event {
+        DialogueEvent::Started { npc, .. } => Some((Goal::Talk(*npc), 1)),
+        _ => None,
+    }));
+    happened.extend(chest_events.iter().map(|event| (Goal::OpenChest(event.id.clone()), 1)));
+    happened.extend(trigger_events.iter().map(|event| (Goal::Reach(event.id.clone()), 1)));
+
+    for quest in quests.0.iter() {
+        // only touch the save data when a quest actually advances, it is written on change.
+        // quests can also start out with every objective met already.
+        let advances = match save.quests.get(&quest.id) {
+            Some(progress) if !progress.completed => {
+                let count = |index: usize| progress.counts.get(index).copied().unwrap_or(0);
+                quest.objectives.iter().enumerate().all(|(index, objective)| count(index) >= objective.count)
+                    || quest.objectives.iter().enumerate().any(|(index, objective)| {
+                        count(index) < objective.count && happened.iter().any(|(goal, _)| *goal == objective.goal)
+                    })
+            }
+            _ => false,
+        };
+        if !advances {
+            continue;
+        }
+
+        let progress = save.quests.get_mut(&quest.id).unwrap();
+        // quests.ron may have gained objectives since the game was saved
+        progress.counts.resize(quest.objectives.len(), 0);
+        for (objective, count) in quest.objectives.iter().zip(progress.counts.iter_mut()) {
+            for (goal, amount) in happened.iter() {
+                if *goal == objective.goal {
+                    *count = (*count + amount).min(objective.count);
+                }
+            }
+        }
+
+        if quest.objectives.iter().zip(progress.counts.iter()).all(|(objective, count)| *count >= objective.count) {
+            progress.completed = true;
+            for (kind, count) in quest.reward.iter() {
+                let left = inventory.add(*kind, *count);
+                if left > 0 {
+                    println!("Inventory full, lost {} reward items of quest '{}'", left, quest.id);
+                }
+            }
+            events.send(QuestEvent::Completed { id: quest.id.clone() });
+        }
+    }
+}
+
+fn quest_tracker_update(
+    quests: Res<Quests>,
+    save: Res<SaveData>,
+    font: Res<QuestFont>,
+    mut query: Query<&mut Text, With<QuestTrackerText>>,
+) {
+    if !save.is_changed() {
+        return;
+    }
+    let style = |size: f32, color: Color| TextStyle {
+        font: font.0.clone(),
+        font_size: size,
+        color,
+    };
+
+    let mut sections = Vec::new();
+    for quest in quests.0.iter() {
+        let progress = match save.quests.get(&quest.id) {
+            Some(progress) if !progress.completed => progress,
+            _ => continue,
+        };
+        sections.push(TextSection {
+            value: format!("{}\n", quest.title),
+            style: style(18.0, TITLE_COLOR),
+        });
+        for (index, objective) in quest.objectives.iter().enumerate() {
+            let count = progress.counts.get(index).copied().unwrap_or(0);
+            let value = if objective.count > 1 {
+                format!("  {} {}/{}\n", objective.text, count, objective.count)
+            } else {
+                format!("  {}\n", objective.text)
+            };
+            sections.push(TextSection {
+                value,
+                style: style(
+                    14.0,
+                    if count >= objective.count {
+                        OBJECTIVE_DONE_COLOR
+                    } else {
+                        OBJECTIVE_COLOR
+                    },
+                ),
+            });
+        }
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections = sections.clone();
+    }
+}
@@ -0,0 +1,90 @@
+// persistent game state. native builds write a ron file next to the executable's
+// working directory, wasm builds keep the same ron text in the browser's localStorage.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::quest::QuestProgress;
+use crate::AppState;
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_FILE: &str = "save.ron";
+#[cfg(target_arch = "wasm32")]
+const SAVE_KEY: &str = "zhneeshgame.save";
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct SaveData {
+    // ids (from level.ron) of chests that have been looted already
+    #[serde(default)]
+    pub opened_chests: BTreeSet<String>,
+    // started and finished quests by id (from quests.ron)
+    #[serde(default)]
+    pub quests: BTreeMap<String, QuestProgress>,
+}
+
+impl SaveData {
+    pub fn load() -> Self {
+        match read_raw() {
+            Some(raw) => ron::de::from_str(&raw).unwrap_or_else(|e| {
+                println!("Save data is malformed, starting fresh: {}", e);
+                SaveData::default()
+            }),
+            None => SaveData::default(),
+        }
+    }
+
+    pub fn store(&self) {
+        match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(raw) => write_raw(&raw),
+            Err(e) => println!("Could not serialize save data: {}", e),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_raw() -> Option<String> {
+    std::fs::read_to_string(SAVE_FILE).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_raw(raw: &str) {
+    if let Err(e) = std::fs::write(SAVE_FILE, raw) {
+        println!("Could not write {}: {}", SAVE_FILE, e);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_raw() -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    storage.get_item(SAVE_KEY).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_raw(raw: &str) {
+    if let Some(Ok(Some(storage))) = web_sys::window().map(|window| window.local_storage()) {
+        if storage.set_item(SAVE_KEY, raw).is_err() {
+            println!("Could not write save data to localStorage");
+        }
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(SaveData::load())
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(save_handler.system()),
+            );
+    }
+}
+
+// write the save data back whenever a system modified it.
+fn save_handler(save: Res<SaveData>) {
+    if save.is_changed() {
+        save.store();
+    }
+}
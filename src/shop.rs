@@ -0,0 +1,437 @@
+// vendor shops: stock and prices from assets/data/shop.ron, paid in coins from the inventory.
+// a shop is opened by a dialogue choice (DialogueAction::OpenShop) and blocks player input
+// until it is closed again.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::dialogue::{DialogueAction, DialogueEvent};
+use crate::inventory::{Inventory, ItemKind};
+use crate::{AppState, KEY_INTERACT};
+
+const SHOP_SOURCE: &str = include_str!("../assets/data/shop.ron");
+
+const PANEL_WIDTH: f32 = 360.0;
+const PANEL_HEIGHT: f32 = 300.0;
+const PANEL_PADDING: f32 = 12.0;
+
+const TITLE_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+const ENTRY_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+const ENTRY_SELECTED_COLOR: Color = Color::WHITE;
+const ENTRY_DISABLED_COLOR: Color = Color::rgb(0.45, 0.45, 0.45);
+
+#[derive(Deserialize)]
+pub struct ShopDesc {
+    pub stock: Vec<ShopEntry>,
+    // what the vendor pays per item when the player sells
+    #[serde(default)]
+    pub buys: HashMap<ItemKind, u32>,
+}
+
+#[derive(Deserialize)]
+pub struct ShopEntry {
+    pub item: ItemKind,
+    pub price: u32,
+    // limited stock, None sells forever
+    #[serde(default)]
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ShopTab {
+    Buy,
+    Sell,
+}
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+// all shops, stock counts go down as the player buys
+pub struct Shops(pub HashMap<String, ShopDesc>);
+
+impl Shops {
+    pub fn load() -> Self {
+        Shops(ron::de::from_str(SHOP_SOURCE).expect("assets/data/shop.ron is malformed"))
+    }
+}
+
+pub struct ActiveShop {
+    pub name: String,
+    pub tab: ShopTab,
+    selected: usize,
+    // result of the last purchase/sale, shown at the bottom of the panel
+    message: String,
+    // the key press confirming the dialogue choice must not buy anything
+    just_opened: bool,
+}
+
+#[derive(Default)]
+pub struct ShopState {
+    pub active: Option<ActiveShop>,
+}
+
+impl ShopState {
+    pub fn is_open(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+struct ShopFont(Handle<Font>);
+
+////////////////////
+// Resources end //
+////////////////////
+
+// all nodes of the shop panel (toggled together)
+#[derive(Debug)]
+struct ShopUi;
+#[derive(Debug)]
+struct ShopTitleText;
+#[derive(Debug)]
+struct ShopListText;
+#[derive(Debug)]
+struct ShopMessageText;
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(Shops::load())
+            .init_resource::<ShopState>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_shop_ui.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(shop_open.system().label("shop_open").after("dialogue_input"))
+                    .with_system(shop_input.system().label("shop_input").after("shop_open"))
+                    .with_system(shop_ui_update.system().after("shop_input")),
+            );
+    }
+}
+
+// items the player can sell here, in a stable order.
+fn sellable(shop: &ShopDesc, inventory: &Inventory) -> Vec<(ItemKind, u32)> {
+    ItemKind::all()
+        .iter()
+        .filter(|kind| inventory.count(**kind) > 0)
+        .filter_map(|kind| shop.buys.get(kind).map(|price| (*kind, *price)))
+        .collect()
+}
+
+fn init_shop_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let hidden = Visible {
+        is_visible: false,
+        is_transparent: true,
+    };
+    let text = |size: f32, color: Color| {
+        Text::with_section(
+            "",
+            TextStyle {
+                font: font.clone(),
+                font_size: size,
+                color,
+            },
+            Default::default(),
+        )
+    };
+
+    // full screen node centering the panel
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            visible: hidden.clone(),
+            ..Default::default()
+        })
+        .insert(ShopUi)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(PANEL_WIDTH), Val::Px(PANEL_HEIGHT)),
+                        padding: Rect::all(Val::Px(PANEL_PADDING)),
+                        // ui columns grow upwards, reverse to lay out text top to bottom
+                        flex_direction: FlexDirection::ColumnReverse,
+                        justify_content: JustifyContent::FlexStart,
+                        ..Default::default()
+                    },
+                    material: materials.add(Color::rgba(0.2, 0.15, 0.1, 0.9).into()),
+                    visible: hidden.clone(),
+                    ..Default::default()
+                })
+                .insert(ShopUi)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: text(20.0, TITLE_COLOR),
+                            visible: hidden.clone(),
+                            ..Default::default()
+                        })
+                        .insert(ShopUi)
+                        .insert(ShopTitleText);
+                    parent
+                        .spawn_bundle(TextBundle {
+                            style: Style {
+                                margin: Rect {
+                                    top: Val::Px(10.0),
+                                    ..Default::default()
+                                },
+                                flex_grow: 1.0,
+                                ..Default::default()
+                            },
+                            text: Text::default(),
+                            visible: hidden.clone(),
+                            ..Default::default()
+                        })
+                        .insert(ShopUi)
+                        .insert(ShopListText);
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: text(14.0, ENTRY_COLOR),
+                            visible: hidden.clone(),
+                            ..Default::default()
+                        })
+                        .insert(ShopUi)
+                        .insert(ShopMessageText);
+                });
+        });
+
+    commands.insert_resource(ShopFont(font));
+}
+
+fn shop_open(
+    mut events: EventReader<DialogueEvent>,
+    mut state: ResMut<ShopState>,
+    shops: Res<Shops>,
+) {
+    for event in events.iter() {
+        if let DialogueEvent::Action {
+            action: DialogueAction::OpenShop(name),
+            ..
+        } = event
+        {
+            if !shops.0.contains_key(name) {
+                println!("Unknown shop '{}'", name);
+                continue;
+            }
+            state.active = Some(ActiveShop {
+                name: name.clone(),
+                tab: ShopTab::Buy,
+                selected: 0,
+                message: String::new(),
+                just_opened: true,
+            });
+        }
+    }
+}
+
+fn shop_input(
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<ShopState>,
+    mut shops: ResMut<Shops>,
+    mut inventory: ResMut<Inventory>,
+) {
+    let active = match state.active.as_mut() {
+        Some(active) => active,
+        None => return,
+    };
+    if active.just_opened {
+        active.just_opened = false;
+        return;
+    }
+    if keys.just_pressed(KeyCode::Escape) {
+        state.active = None;
+        return;
+    }
+    let shop = match shops.0.get_mut(&active.name) {
+        Some(shop) => shop,
+        None => {
+            state.active = None;
+            return;
+        }
+    };
+
+    if keys.just_pressed(KeyCode::Tab)
+        || keys.just_pressed(KeyCode::Left)
+        || keys.just_pressed(KeyCode::Right)
+    {
+        active.tab = match active.tab {
+            ShopTab::Buy => ShopTab::Sell,
+            ShopTab::Sell => ShopTab::Buy,
+        };
+        active.selected = 0;
+        active.message.clear();
+    }
+
+    let entries = match active.tab {
+        ShopTab::Buy => shop.stock.len(),
+        ShopTab::Sell => sellable(shop, &inventory).len(),
+    };
+    if entries == 0 {
+        return;
+    }
+    // the sell list shrinks when the last item of a kind is sold
+    active.selected = active.selected.min(entries - 1);
+    if keys.just_pressed(KeyCode::Up) || keys.just_pressed(KeyCode::W) {
+        active.selected = (active.selected + entries - 1) % entries;
+    }
+    if keys.just_pressed(KeyCode::Down) || keys.just_pressed(KeyCode::S) {
+        active.selected = (active.selected + 1) % entries;
+    }
+
+    if !(keys.just_pressed(KEY_INTERACT) || keys.just_pressed(KeyCode::Return)) {
+        return;
+    }
+    let coins = inventory.count(ItemKind::Coin);
+    match active.tab {
+        ShopTab::Buy => {
+            let entry = &mut shop.stock[active.selected];
+            active.message = if entry.count == Some(0) {
+                "Sold out.".to_string()
+            } else if coins < entry.price {
+                "Not enough coins.".to_string()
+            } else {
+                // pay first, spending a whole stack of coins frees a slot
+                inventory.remove(ItemKind::Coin, entry.price);
+                if inventory.can_add(entry.item, 1) {
+                    inventory.add(entry.item, 1);
+                    if let Some(count) = entry.count.as_mut() {
+                        *count -= 1;
+                    }
+                    format!("Bought {} for {} coins.", entry.item.name(), entry.price)
+                } else {
+                    inventory.add(ItemKind::Coin, entry.price);
+                    "Your inventory is full.".to_string()
+                }
+            };
+        }
+        ShopTab::Sell => {
+            let (kind, price) = sellable(shop, &inventory)[active.selected];
+            // hand the item over first, selling the last of a stack frees a slot
+            inventory.remove(kind, 1);
+            active.message = if inventory.can_add(ItemKind::Coin, price) {
+                inventory.add(ItemKind::Coin, price);
+                format!("Sold {} for {} coins.", kind.name(), price)
+            } else {
+                inventory.add(kind, 1);
+                "You can't carry any more coins.".to_string()
+            };
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn shop_ui_update(
+    state: Res<ShopState>,
+    shops: Res<Shops>,
+    inventory: Res<Inventory>,
+    font: Res<ShopFont>,
+    mut visibles: Query<&mut Visible, With<ShopUi>>,
+    mut texts: QuerySet<(
+        Query<&mut Text, With<ShopTitleText>>,
+        Query<&mut Text, With<ShopListText>>,
+        Query<&mut Text, With<ShopMessageText>>,
+    )>,
+) {
+    if !state.is_changed() && !inventory.is_changed() {
+        return;
+    }
+    for mut visible in visibles.iter_mut() {
+        visible.is_visible = state.is_open();
+    }
+
+    let active = match state.active.as_ref() {
+        Some(active) => active,
+        None => return,
+    };
+    let shop = match shops.0.get(&active.name) {
+        Some(shop) => shop,
+        None => return,
+    };
+    let coins = inventory.count(ItemKind::Coin);
+
+    for mut text in texts.q0_mut().iter_mut() {
+        text.sections[0].value = format!(
+            "{}   (Tab to switch)   Coins: {}",
+            match active.tab {
+                ShopTab::Buy => "Buy",
+                ShopTab::Sell => "Sell",
+            },
+            coins
+        );
+    }
+
+    // (label, affordable)
+    let rows: Vec<(String, bool)> = match active.tab {
+        ShopTab::Buy => shop
+            .stock
+            .iter()
+            .map(|entry| {
+                let label = match entry.count {
+                    Some(count) => format!("{} ({} left) - {}", entry.item.name(), count, entry.price),
+                    None => format!("{} - {}", entry.item.name(), entry.price),
+                };
+                (label, entry.count != Some(0) && coins >= entry.price)
+            })
+            .collect(),
+        ShopTab::Sell => sellable(shop, &inventory)
+            .into_iter()
+            .map(|(kind, price)| (format!("{} x{} - {}", kind.name(), inventory.count(kind), price), true))
+            .collect(),
+    };
+    let selected = active.selected.min(rows.len().saturating_sub(1));
+    for mut text in texts.q1_mut().iter_mut() {
+        text.sections = if rows.is_empty() {
+            vec![TextSection {
+                value: "Nothing to trade.".to_string(),
+                style: TextStyle {
+                    font: font.0.clone(),
+                    font_size: 16.0,
+                    color: ENTRY_DISABLED_COLOR,
+                },
+            }]
+        } else {
+            rows.iter()
+                .enumerate()
+                .map(|(index, (label, enabled))| TextSection {
+                    value: format!("{} {}\n", if index == selected { ">" } else { " " }, label),
+                    style: TextStyle {
+                        font: font.0.clone(),
+                        font_size: 16.0,
+                        color: if !enabled {
+                            ENTRY_DISABLED_COLOR
+                        } else if index == selected {
+                            ENTRY_SELECTED_COLOR
+                        } else {
+                            ENTRY_COLOR
+                        },
+                    },
+                })
+                .collect()
+        };
+    }
+
+    for mut text in texts.q2_mut().iter_mut() {
+        text.sections[0].value = if active.message.is_empty() {
+            "E: trade   Esc: leave".to_string()
+        } else {
+            active.message.clone()
+        };
+    }
+}
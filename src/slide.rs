@@ -0,0 +1,102 @@
+// the player's slide. the controller (controller.rs) starts and ends it, this shrinks the
+// player's collider to fit under low obstacles while it lasts. the body is lowered so its
+// feet stay on the ground, and the contact sensors move along with the feet.
+//
+// a head sensor covers the room the standing body needs above the sliding one. as long as
+// something is in there, the slide can't end (see Contacts::under_ceiling).
+
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::controller::{Controller, MoveState};
+use crate::ground::{self, ContactSensor};
+use crate::AppState;
+
+// height of the sliding collider, relative to the standing one
+const SLIDE_HEIGHT_RATIO: f32 = 0.5;
+
+//////////////////////
+// Components start
+//////////////////////
+
+#[derive(Debug)]
+pub struct SlideShape {
+    // of the standing collider
+    half_size: Vec2,
+    lowered: bool,
+}
+
+impl SlideShape {
+    pub fn new(half_size: Vec2) -> Self {
+        SlideShape {
+            half_size,
+            lowered: false,
+        }
+    }
+
+    fn slide_half_height(&self) -> f32 {
+        self.half_size.y * SLIDE_HEIGHT_RATIO
+    }
+}
+
+//////////////////////
+// Components end //
+//////////////////////
+
+// adds the head sensor to a body that can slide, next to the ones from ground::spawn_contact_sensors
+pub fn spawn_head_sensor(parent: &mut ChildBuilder, half_size: Vec2) {
+    let slide_half_height = half_size.y * SLIDE_HEIGHT_RATIO;
+    // standing, it sits in the upper part of the body. lowered for a slide, it fills the
+    // space between the top of the sliding body and the top of the standing one.
+    ground::spawn_sensor(
+        parent,
+        ContactSensor::Head,
+        Vec2::new(0.0, slide_half_height),
+        Vec2::new(half_size.x * 0.9, half_size.y - slide_half_height),
+    );
+}
+
+pub struct SlidePlugin;
+
+impl Plugin for SlidePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_update(AppState::Ready)
+                .with_system(slide_shape.system().after("controller")),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn slide_shape(
+    mut bodies: Query<(&Controller, &mut SlideShape, &mut CollisionShape, &mut Transform, &Children)>,
+    mut sensors: Query<&mut Transform, (With<ContactSensor>, Without<SlideShape>)>,
+) {
+    for (controller, mut shape, mut collision_shape, mut transform, children) in bodies.iter_mut() {
+        let lower = controller.state == MoveState::Slide;
+        if lower == shape.lowered {
+            continue;
+        }
+        shape.lowered = lower;
+
+        let half_height = if lower {
+            shape.slide_half_height()
+        } else {
+            shape.half_size.y
+        };
+        *collision_shape = CollisionShape::Cuboid {
+            half_extends: Vec3::new(shape.half_size.x, half_height, 1.0),
+            border_radius: Some(0.0),
+        };
+
+        // keep the feet where they are
+        let drop = shape.half_size.y - shape.slide_half_height();
+        let shift = if lower { -drop } else { drop };
+        transform.translation.y += shift;
+        for child in children.iter() {
+            if let Ok(mut sensor) = sensors.get_mut(*child) {
+                sensor.translation.y -= shift;
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// where the player starts. the embedding page (?spawn=Blog) or the command line
+// (--spawn Blog) can ask for a gate label or a spawn id from assets/data/level.ron,
+// so visitors coming back from a page of the website start next to its gate.
+// a defeated player starts over there too, with full health.
+
+use bevy::prelude::*;
+
+use crate::creature::PlayerDefeatedEvent;
+use crate::gate::GateIdentifier;
+use crate::level::LevelData;
+use crate::{AppState, Health, TeleportEvent, PLAYER_MAX_HEALTH};
+
+const SPAWN_PARAM: &str = "spawn";
+// used when nothing is requested
+const DEFAULT_SPAWN: &str = "start";
+// level position of the default spawn, for levels without spawn points
+const FALLBACK_POS: (f32, f32) = (-24.0, 0.0);
+// distance from a gate's left edge to the player, in half tiles
+const GATE_SPAWN_GAP: f32 = 4.0;
+
+//////////////////////
+// Resources start //
+//////////////////////
+
+// gate label or spawn id asked for on startup
+#[derive(Default, Debug)]
+pub struct SpawnRequest(pub Option<String>);
+
+impl SpawnRequest {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read() -> Self {
+        // --spawn Blog or --spawn=Blog
+        let flag = format!("--{}", SPAWN_PARAM);
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == flag {
+                return SpawnRequest(args.next());
+            }
+            if let Some(value) = arg.strip_prefix(&format!("{}=", flag)) {
+                return SpawnRequest(Some(value.to_string()));
+            }
+        }
+        SpawnRequest(None)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn read() -> Self {
+        let value = web_sys::window()
+            .and_then(|window| window.location().search().ok())
+            .and_then(|search| web_sys::UrlSearchParams::new_with_str(&search).ok())
+            .and_then(|params| params.get(SPAWN_PARAM));
+        SpawnRequest(value)
+    }
+
+    // level position (in half tiles) the player starts at
+    pub fn position(&self, level: &LevelData) -> (f32, f32) {
+        if let Some(name) = self.0.as_deref() {
+            let gate = GateIdentifier::from_label(name);
+            if let Some(desc) = level.gates.iter().find(|desc| gate != GateIdentifier::None && desc.gate == gate) {
+                // beside the gate rather than inside it, so it doesn't prompt right away
+                return (desc.pos.0 - desc.size.0 / 2.0 - GATE_SPAWN_GAP, desc.pos.1);
+            }
+            if let Some(spawn) = level.spawns.iter().find(|spawn| spawn.id == name) {
+                return spawn.pos;
+            }
+            println!("Unknown spawn '{}', starting at the default spawn", name);
+        }
+        level
+            .spawns
+            .iter()
+            .find(|spawn| spawn.id == DEFAULT_SPAWN)
+            .map(|spawn| spawn.pos)
+            .unwrap_or(FALLBACK_POS)
+    }
+}
+
+////////////////////
+// Resources end //
+////////////////////
+
+pub struct SpawnPlugin;
+
+impl Plugin for SpawnPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the camera is put on top of the player by camera.rs
+        app.insert_resource(SpawnRequest::read())
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(player_respawn.system().after("damage")),
+            );
+    }
+}
+
+fn player_respawn(
+    mut events: EventReader<PlayerDefeatedEvent>,
+    request: Res<SpawnRequest>,
+    level: Res<LevelData>,
+    mut players: Query<&mut Health>,
+    mut teleport_events: EventWriter<TeleportEvent>,
+) {
+    for event in events.iter() {
+        if let Ok(mut health) = players.get_mut(event.player) {
+            println!("Player defeated, back to the spawn point");
+            health.0 = PLAYER_MAX_HEALTH;
+            teleport_events.send(TeleportEvent {
+                pos: request.position(&level),
+            });
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// invisible trigger zones from the level data. they raise a TriggerEvent with their id
+// whenever the player walks into them (quests, cutscenes, ...).
+
+use bevy::prelude::*;
+use heron::prelude::*;
+
+use crate::level::{self, LevelData};
+use crate::{collided_with_player, AppState, Layer, StaticEntities, TILE_UNIT_TRANSLATION};
+
+#[derive(Debug, Clone)]
+pub struct TriggerEvent {
+    pub id: String,
+}
+
+#[derive(Debug)]
+pub struct Trigger {
+    pub id: String,
+}
+
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<TriggerEvent>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Setup)
+                    .with_system(init_triggers.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(trigger_handler.system()),
+            );
+    }
+}
+
+fn init_triggers(mut commands: Commands, level: Res<LevelData>) {
+    for desc in level.triggers.iter() {
+        commands
+            .spawn()
+            .insert(Transform::from_translation(level::to_world(desc.pos, 1.0)))
+            .insert(GlobalTransform::default())
+            .insert(Trigger { id: desc.id.clone() })
+            .insert(RigidBody::Sensor)
+            .insert(Layer::Sensors.collision_layers())
+            .insert(CollisionShape::Cuboid {
+                half_extends: Vec3::new(
+                    desc.size.0 * TILE_UNIT_TRANSLATION / 2.0,
+                    desc.size.1 * TILE_UNIT_TRANSLATION / 2.0,
+                    1.0,
+                ),
+                border_radius: None,
+            });
+    }
+}
+
+fn trigger_handler(
+    mut events: EventReader<CollisionEvent>,
+    mut trigger_events: EventWriter<TriggerEvent>,
+    static_entities: Res<StaticEntities>,
+    query: Query<&Trigger>,
+) {
+    for event in events.iter() {
+        if let CollisionEvent::Started(d1, d2) = event {
+            if let Some(entity) = collided_with_player(d1, d2, &static_entities) {
+                if let Ok(trigger) = query.get(entity) {
+                    trigger_events.send(TriggerEvent { id: trigger.id.clone() });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+// javascript api for the embedding website (wasm only).
+//
+//   import init, { start, stop, onGate, onNavigate, teleport, highlightGate } from "./wasm_lib.js";
+//   await init();
+//   onGate((gate, entered) => { ... });    // gate is the label: "Home", "Blog", "Markets", ...
+//   onNavigate((gate, target) => { ... }); // the player went through a gate, target is e.g. "/blog"
+//   start();
+//   new ResizeObserver(() => resize(box.clientWidth, box.clientHeight)).observe(box);
+//
+// calls from javascript are queued and picked up by the game once per frame.
+
+use std::cell::RefCell;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::gate::{GateEvent, GateHandler, GateIdentifier, GateLink, HighlightGateEvent};
+use crate::{AppState, TeleportEvent};
+
+enum JsCommand {
+    Stop,
+    Resize(f32, f32),
+    Teleport(f32, f32),
+    Highlight(GateIdentifier, bool),
+}
+
+#[derive(Default)]
+struct JsState {
+    started: bool,
+    gate_link: Option<GateLink>,
+    gate_callback: Option<js_sys::Function>,
+    navigate_callback: Option<js_sys::Function>,
+    commands: Vec<JsCommand>,
+}
+
+// wasm runs the game and the page's scripts on the same thread
+thread_local! {
+    static JS_STATE: RefCell<JsState> = RefCell::new(JsState::default());
+}
+
+pub fn register_gate_link(link: GateLink) {
+    JS_STATE.with(|state| state.borrow_mut().gate_link = Some(link));
+}
+
+fn push_command(command: JsCommand) {
+    JS_STATE.with(|state| state.borrow_mut().commands.push(command));
+}
+
+// gates lead to the page's own navigation
+pub struct JsGateHandler;
+
+impl GateHandler for JsGateHandler {
+    fn navigate(&self, gate: GateIdentifier) {
+        let callback = JS_STATE.with(|state| state.borrow().navigate_callback.clone());
+        match callback {
+            Some(callback) => {
+                let result = callback.call2(
+                    &JsValue::NULL,
+                    &JsValue::from_str(gate.label()),
+                    &JsValue::from_str(gate.target()),
+                );
+                if result.is_err() {
+                    println!("Navigate callback threw for {:?}", gate);
+                }
+            }
+            None => println!("No onNavigate callback registered for gate {:?}", gate),
+        }
+    }
+}
+
+////////////////////////////////////////
+// exported functions start
+////////////////////////////////////////
+
+// starts the game on the page's canvas. the game can only run once per page load, also
+// after stop().
+#[wasm_bindgen]
+pub fn start() {
+    let started = JS_STATE.with(|state| std::mem::replace(&mut state.borrow_mut().started, true));
+    if started {
+        web_sys::console::warn_1(&JsValue::from_str("The game has already run on this page, reload it to start again"));
+    } else {
+        crate::run();
+    }
+}
+
+// ends the game loop for good, start() doesn't bring it back.
+#[wasm_bindgen]
+pub fn stop() {
+    push_command(JsCommand::Stop);
+}
+
+// callback(gate: string, entered: bool), called whenever the player enters or leaves a gate.
+#[wasm_bindgen(js_name = onGate)]
+pub fn on_gate(callback: js_sys::Function) {
+    JS_STATE.with(|state| state.borrow_mut().gate_callback = Some(callback));
+}
+
+// callback(gate: string, target: string), called when the player goes through a gate.
+// target is the page path, e.g. "/blog".
+#[wasm_bindgen(js_name = onNavigate)]
+pub fn on_navigate(callback: js_sys::Function) {
+    JS_STATE.with(|state| state.borrow_mut().navigate_callback = Some(callback));
+}
+
+// sets the canvas size in css pixels, the view is scaled to it (see display.rs).
+#[wasm_bindgen]
+pub fn resize(width: f32, height: f32) {
+    push_command(JsCommand::Resize(width, height));
+}
+
+// moves the player to a level position (in half tiles, like assets/data/level.ron).
+#[wasm_bindgen]
+pub fn teleport(x: f32, y: f32) {
+    push_command(JsCommand::Teleport(x, y));
+}
+
+// shows or hides the outline of a gate ("Home", "Blog", "Markets", "About", "Portfolio").
+#[wasm_bindgen(js_name = highlightGate)]
+pub fn highlight_gate(gate: &str, on: bool) {
+    push_command(JsCommand::Highlight(GateIdentifier::from_label(gate), on));
+}
+
+// name of the gate the player currently stands in, "None" outside of gates.
+// kept for pages polling instead of using onGate.
+#[wasm_bindgen(js_name = currentGate)]
+pub fn current_gate() -> String {
+    JS_STATE.with(|state| {
+        let gate = state
+            .borrow()
+            .gate_link
+            .as_ref()
+            .map(|link| link.get())
+            .unwrap_or(GateIdentifier::None);
+        gate.label().to_string()
+    })
+}
+
+////////////////////////////////////////
+// exported functions end
+////////////////////////////////////////
+
+pub struct WebPlugin;
+
+impl Plugin for WebPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(js_stop.system().label("js_stop"))
+            .add_system(js_resize.system().label("js_resize"))
+            .add_system_set(
+                SystemSet::on_update(AppState::Ready)
+                    .with_system(js_commands.system().after("js_stop").after("js_resize"))
+                    .with_system(js_gate_callback.system()),
+            );
+    }
+}
+
+// stop and resize are honoured in every state, the rest waits until the level is set up.
+fn js_stop(mut exit: EventWriter<AppExit>) {
+    let stop = JS_STATE.with(|state| {
+        let commands = &mut state.borrow_mut().commands;
+        let stop = commands.iter().any(|command| matches!(command, JsCommand::Stop));
+        commands.retain(|command| !matches!(command, JsCommand::Stop));
+        stop
+    });
+    if stop {
+        exit.send(AppExit);
+    }
+}
+
+fn js_resize(mut windows: ResMut<Windows>) {
+    let size = JS_STATE.with(|state| {
+        let commands = &mut state.borrow_mut().commands;
+        let size = commands.iter().rev().find_map(|command| match *command {
+            JsCommand::Resize(width, height) => Some((width, height)),
+            _ => None,
+        });
+        commands.retain(|command| !matches!(command, JsCommand::Resize(..)));
+        size
+    });
+    if let (Some((width, height)), Some(window)) = (size, windows.get_primary_mut()) {
+        window.set_resolution(width, height);
+    }
+}
+
+fn js_commands(
+    mut teleport_events: EventWriter<TeleportEvent>,
+    mut highlight_events: EventWriter<HighlightGateEvent>,
+) {
+    // a stop or resize queued by a callback after js_stop and js_resize ran is left for the
+    // next frame
+    let commands = JS_STATE.with(|state| {
+        let queue = &mut state.borrow_mut().commands;
+        let (left, commands): (Vec<_>, Vec<_>) = std::mem::take(queue)
+            .into_iter()
+            .partition(|command| matches!(command, JsCommand::Stop | JsCommand::Resize(..)));
+        *queue = left;
+        commands
+    });
+    for command in commands {
+        match command {
+            JsCommand::Stop | JsCommand::Resize(..) => {}
+            JsCommand::Teleport(x, y) => teleport_events.send(TeleportEvent { pos: (x, y) }),
+            JsCommand::Highlight(gate, on) => highlight_events.send(HighlightGateEvent { gate, on }),
+        }
+    }
+}
+
+fn js_gate_callback(mut events: EventReader<GateEvent>) {
+    for event in events.iter() {
+        let (gate, entered) = match *event {
+            GateEvent::Entered(gate) => (gate, true),
+            GateEvent::Left(gate) => (gate, false),
+        };
+        // clone the callback out, it may call back into the api
+        let callback = JS_STATE.with(|state| state.borrow().gate_callback.clone());
+        if let Some(callback) = callback {
+            let result = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from_str(gate.label()),
+                &JsValue::from_bool(entered),
+            );
+            if result.is_err() {
+                println!("Gate callback threw for {:?}", gate);
+            }
+        }
+    }
+}